@@ -0,0 +1,284 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use axum::extract::Request;
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::StatusCode;
+use axum::middleware::{from_fn, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::checks::force_success_file_check::DEFAULT_FORCE_SUCCESS_FILE_PATH;
+use crate::checks::mtc_file_check::DEFAULT_MTC_FILE_PATH;
+use crate::options::Options;
+use crate::util::maintenance_info::{
+    read_maintenance_file, write_maintenance_file, MaintenanceInfo,
+};
+
+/// The prefix of all admin api routes, which cannot be used by status endpoints.
+pub(crate) const ADMIN_PATH_PREFIX: &str = "/admin/";
+
+/// The author of maintenances that are enabled without naming an author.
+const DEFAULT_AUTHOR: &str = "admin api";
+
+/// A file that is toggled through the admin api.
+#[derive(Clone, Copy, Debug)]
+enum AdminToggle {
+    /// The maintenance file.
+    Maintenance,
+    /// The force success file.
+    ForceSuccess,
+    /// The drain file.
+    Drain,
+}
+
+/// The request to enable a toggle, all details are optional.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EnableRequest {
+    /// The reason why the toggle is enabled.
+    pub reason: Option<String>,
+    /// The author who enables the toggle.
+    pub by: Option<String>,
+    /// The seconds after which the toggle is disabled again.
+    pub ttl: Option<u64>,
+}
+
+/// The current state of a toggle.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ToggleStatus {
+    /// If the toggle is currently enabled.
+    pub enabled: bool,
+    /// The details of the enabled toggle.
+    #[serde(flatten)]
+    pub info: MaintenanceInfo,
+}
+
+/// The settings of the admin api, which toggles the maintenance, force
+/// success and drain files of the running instance.
+#[derive(Clone, Debug)]
+pub(crate) struct AdminApiSettings {
+    /// The bearer tokens which are accepted by the admin api.
+    tokens: Vec<String>,
+    /// The path of the maintenance file.
+    mtc_file_path: PathBuf,
+    /// The path of the force success file.
+    force_success_file_path: PathBuf,
+    /// The path of the drain file, if draining is enabled.
+    drain_file_path: Option<PathBuf>,
+}
+
+impl AdminApiSettings {
+    /// Constructs the admin api settings from the given options. Returns None if the admin
+    /// api is not enabled. Fails if the token file cannot be read or contains no tokens.
+    pub fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        let Some(token_file_path) = &options.admin_token_file_path else {
+            if options.admin_bind_host.is_some() {
+                bail!("the admin api requires an admin token file");
+            }
+            return Ok(None);
+        };
+        let tokens = read_admin_tokens(token_file_path)?;
+
+        let mtc_file_path = options
+            .mtc_check_file_path
+            .clone()
+            .unwrap_or_else(|| String::from(DEFAULT_MTC_FILE_PATH));
+        let force_success_file_path = options
+            .force_success_file_path
+            .clone()
+            .unwrap_or_else(|| String::from(DEFAULT_FORCE_SUCCESS_FILE_PATH));
+        Ok(Some(Self {
+            tokens,
+            mtc_file_path: PathBuf::from(mtc_file_path),
+            force_success_file_path: PathBuf::from(force_success_file_path),
+            drain_file_path: options.drain_file_path.as_ref().map(PathBuf::from),
+        }))
+    }
+
+    /// Builds the routes of the admin api, which all require a valid bearer token.
+    /// The drain route is only available if a drain file is configured.
+    pub fn router(self) -> Router {
+        let toggle_route = |toggle: AdminToggle| {
+            get(get_toggle)
+                .post(enable_toggle)
+                .delete(disable_toggle)
+                .layer(Extension(toggle))
+        };
+        let mut router = Router::new()
+            .route("/admin/maintenance", toggle_route(AdminToggle::Maintenance))
+            .route(
+                "/admin/force-success",
+                toggle_route(AdminToggle::ForceSuccess),
+            );
+        if self.drain_file_path.is_some() {
+            router = router.route("/admin/drain", toggle_route(AdminToggle::Drain));
+        }
+        router
+            .route_layer(from_fn(require_token))
+            .layer(Extension(self))
+    }
+
+    fn file_path(&self, toggle: AdminToggle) -> &PathBuf {
+        match toggle {
+            AdminToggle::Maintenance => &self.mtc_file_path,
+            AdminToggle::ForceSuccess => &self.force_success_file_path,
+            AdminToggle::Drain => self
+                .drain_file_path
+                .as_ref()
+                .expect("drain route is only registered with a drain file"),
+        }
+    }
+
+    /// Checks if the given bearer token is accepted. The tokens are compared
+    /// in constant time, so that they cannot be guessed by response times.
+    fn accepts_token(&self, token: &str) -> bool {
+        self.tokens.iter().fold(false, |accepted, expected_token| {
+            accepted | constant_time_eq(expected_token.as_bytes(), token.as_bytes())
+        })
+    }
+}
+
+/// Reads the bearer tokens from the given admin token file, one token per line. Empty
+/// lines and lines starting with `#` are ignored. Fails if the file contains no tokens.
+pub(crate) fn read_admin_tokens(token_file_path: &str) -> anyhow::Result<Vec<String>> {
+    let token_file = std::fs::read_to_string(token_file_path)
+        .with_context(|| format!("unable to read admin token file {}", token_file_path))?;
+    let tokens: Vec<String> = token_file
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    if tokens.is_empty() {
+        bail!("admin token file {} contains no tokens", token_file_path);
+    }
+    Ok(tokens)
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0, |difference, (left, right)| difference | (left ^ right))
+            == 0
+}
+
+/// Rejects requests without a bearer token that is accepted by the admin api.
+async fn require_token(
+    Extension(settings): Extension<AdminApiSettings>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) if settings.accepts_token(token.trim()) => next.run(request).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(WWW_AUTHENTICATE, "Bearer")],
+            "missing or invalid bearer token",
+        )
+            .into_response(),
+    }
+}
+
+async fn get_toggle(
+    Extension(settings): Extension<AdminApiSettings>,
+    Extension(toggle): Extension<AdminToggle>,
+) -> Response {
+    match read_maintenance_file(settings.file_path(toggle)).await {
+        Ok(Some(info)) if !info.has_ended() => toggle_status(true, info),
+        Ok(_) => toggle_status(false, MaintenanceInfo::default()),
+        Err(error) => internal_error(toggle, error),
+    }
+}
+
+async fn enable_toggle(
+    Extension(settings): Extension<AdminApiSettings>,
+    Extension(toggle): Extension<AdminToggle>,
+    request: Option<Json<EnableRequest>>,
+) -> Response {
+    let Json(request) = request.unwrap_or_default();
+    if request.ttl == Some(0) {
+        return (StatusCode::BAD_REQUEST, "ttl must be positive").into_response();
+    }
+    let now = Timestamp::now();
+    let until = match request
+        .ttl
+        .map(|ttl| now.checked_add(Duration::from_secs(ttl)))
+    {
+        Some(Ok(until)) => Some(until.to_string()),
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, "ttl is too large").into_response(),
+        None => None,
+    };
+    let info = MaintenanceInfo {
+        reason: request.reason,
+        by: Some(request.by.unwrap_or_else(|| String::from(DEFAULT_AUTHOR))),
+        since: Some(now.to_string()),
+        until,
+    };
+    match write_maintenance_file(settings.file_path(toggle), &info).await {
+        Ok(()) => {
+            log::info!("enabled {:?} through admin api: {:?}", toggle, info);
+            toggle_status(true, info)
+        }
+        Err(error) => internal_error(toggle, error),
+    }
+}
+
+async fn disable_toggle(
+    Extension(settings): Extension<AdminApiSettings>,
+    Extension(toggle): Extension<AdminToggle>,
+) -> Response {
+    match fs::remove_file(settings.file_path(toggle)).await {
+        Ok(()) => log::info!("disabled {:?} through admin api", toggle),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => return internal_error(toggle, error),
+    }
+    toggle_status(false, MaintenanceInfo::default())
+}
+
+fn toggle_status(enabled: bool, info: MaintenanceInfo) -> Response {
+    Json(ToggleStatus { enabled, info }).into_response()
+}
+
+fn internal_error(toggle: AdminToggle, error: std::io::Error) -> Response {
+    log::error!("unable to toggle {:?} through admin api: {}", toggle, error);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("unable to access file: {}", error),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(tokens: &[&str]) -> AdminApiSettings {
+        AdminApiSettings {
+            tokens: tokens.iter().map(|token| token.to_string()).collect(),
+            mtc_file_path: PathBuf::from(DEFAULT_MTC_FILE_PATH),
+            force_success_file_path: PathBuf::from(DEFAULT_FORCE_SUCCESS_FILE_PATH),
+            drain_file_path: None,
+        }
+    }
+
+    #[test]
+    fn accepts_configured_tokens_only() {
+        let settings = settings(&["first-token", "second-token"]);
+        assert!(settings.accepts_token("first-token"));
+        assert!(settings.accepts_token("second-token"));
+        assert!(!settings.accepts_token("first"));
+        assert!(!settings.accepts_token(""));
+    }
+}
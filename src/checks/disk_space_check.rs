@@ -0,0 +1,284 @@
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use crate::options::Options;
+use crate::status::status_checker::{CheckSeverity, StatusCheckResult, StatusChecker};
+use crate::util::threshold::{format_bytes, percent_of, Threshold};
+
+/// The space and inode usage of a single file system.
+#[derive(Debug, Clone, PartialEq)]
+struct DiskUsage {
+    free_bytes: u64,
+    total_bytes: u64,
+    free_inodes: u64,
+    total_inodes: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct DiskSpaceCheck {
+    paths: Vec<PathBuf>,
+    critical_free_space: Option<Threshold>,
+    warning_free_space: Option<Threshold>,
+    critical_free_inodes: Option<Threshold>,
+    warning_free_inodes: Option<Threshold>,
+}
+
+#[async_trait]
+impl StatusChecker for DiskSpaceCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        match options.disk_check_paths.to_owned() {
+            None => Ok(None),
+            Some(paths) if paths.is_empty() => Ok(None),
+            Some(paths) => Ok(Some(Self {
+                paths,
+                critical_free_space: options.disk_check_min_free_space,
+                warning_free_space: options.disk_check_warn_free_space,
+                critical_free_inodes: options.disk_check_min_free_inodes,
+                warning_free_inodes: options.disk_check_warn_free_inodes,
+            })),
+        }
+    }
+
+    fn check_name(&self) -> String {
+        let paths: Vec<_> = self
+            .paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        format!("disk space check {}", paths.join(", "))
+    }
+
+    fn check_id(&self) -> &str {
+        "disk"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        let mut severity = CheckSeverity::Ok;
+        let mut failure_reasons = vec![];
+        for path in &self.paths {
+            log::debug!("checking disk space of {:?}", path);
+            let stat_path = path.clone();
+            let usage = tokio::task::spawn_blocking(move || Self::query_disk_usage(&stat_path))
+                .await?
+                .with_context(|| format!("unable to query disk usage of {}", path.display()))?;
+            if let Some((path_severity, reason)) = self.evaluate_disk_usage(path, &usage) {
+                severity = severity.max(path_severity);
+                failure_reasons.push(reason);
+            }
+        }
+
+        Ok(StatusCheckResult::new_with_severity(
+            severity,
+            failure_reasons.join("; "),
+        ))
+    }
+}
+
+impl DiskSpaceCheck {
+    /// Queries the space and inode usage of the file system the given path is located on.
+    fn query_disk_usage(path: &Path) -> io::Result<DiskUsage> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        let result = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // the field types of statvfs differ between platforms
+        #[allow(clippy::useless_conversion)]
+        let usage = DiskUsage {
+            free_bytes: u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize)),
+            total_bytes: u64::from(stat.f_blocks).saturating_mul(u64::from(stat.f_frsize)),
+            free_inodes: u64::from(stat.f_favail),
+            total_inodes: u64::from(stat.f_files),
+        };
+        Ok(usage)
+    }
+
+    /// Evaluates the given disk usage against the configured thresholds. Returns the
+    /// severity and reason if a critical or warning threshold is undercut.
+    fn evaluate_disk_usage(
+        &self,
+        path: &Path,
+        usage: &DiskUsage,
+    ) -> Option<(CheckSeverity, String)> {
+        let free_space = format!(
+            "{} ({:.1}%) free",
+            format_bytes(usage.free_bytes),
+            percent_of(usage.free_bytes, usage.total_bytes)
+        );
+        let free_inodes = format!(
+            "{} ({:.1}%) inodes free",
+            usage.free_inodes,
+            percent_of(usage.free_inodes, usage.total_inodes)
+        );
+        // some file systems (for example btrfs) do not have a fixed amount of inodes
+        let has_inodes = usage.total_inodes > 0;
+
+        let mut critical_reasons = vec![];
+        if let Some(threshold) = &self.critical_free_space {
+            if threshold.is_undercut_by(usage.free_bytes, usage.total_bytes) {
+                critical_reasons.push(format!(
+                    "{}, minimum is {}",
+                    free_space,
+                    threshold.format_as_bytes()
+                ));
+            }
+        }
+        if let Some(threshold) = &self.critical_free_inodes {
+            if has_inodes && threshold.is_undercut_by(usage.free_inodes, usage.total_inodes) {
+                critical_reasons.push(format!("{}, minimum is {}", free_inodes, threshold));
+            }
+        }
+        if !critical_reasons.is_empty() {
+            let reason = format!("{}: {}", path.display(), critical_reasons.join(", "));
+            return Some((CheckSeverity::Critical, reason));
+        }
+
+        let mut warning_reasons = vec![];
+        if let Some(threshold) = &self.warning_free_space {
+            if threshold.is_undercut_by(usage.free_bytes, usage.total_bytes) {
+                warning_reasons.push(format!(
+                    "{}, warning at {}",
+                    free_space,
+                    threshold.format_as_bytes()
+                ));
+            }
+        }
+        if let Some(threshold) = &self.warning_free_inodes {
+            if has_inodes && threshold.is_undercut_by(usage.free_inodes, usage.total_inodes) {
+                warning_reasons.push(format!("{}, warning at {}", free_inodes, threshold));
+            }
+        }
+        if !warning_reasons.is_empty() {
+            let reason = format!("{}: {}", path.display(), warning_reasons.join(", "));
+            return Some((CheckSeverity::Warning, reason));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_check(
+        critical_free_space: Option<Threshold>,
+        critical_free_inodes: Option<Threshold>,
+    ) -> DiskSpaceCheck {
+        DiskSpaceCheck {
+            paths: vec![PathBuf::from("/var")],
+            critical_free_space,
+            warning_free_space: None,
+            critical_free_inodes,
+            warning_free_inodes: None,
+        }
+    }
+
+    fn usage(free_bytes: u64, free_inodes: u64) -> DiskUsage {
+        DiskUsage {
+            free_bytes,
+            total_bytes: 100 << 30,
+            free_inodes,
+            total_inodes: 1_000_000,
+        }
+    }
+
+    #[test]
+    fn enough_space_returns_no_failure() {
+        let check = make_check(
+            Some(Threshold::Percent(10.0)),
+            Some(Threshold::Percent(10.0)),
+        );
+        let result = check.evaluate_disk_usage(Path::new("/var"), &usage(50 << 30, 500_000));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn low_space_percentage_returns_failure_with_free_values() {
+        let check = make_check(Some(Threshold::Percent(10.0)), None);
+        let (severity, reason) = check
+            .evaluate_disk_usage(Path::new("/var"), &usage(5 << 30, 500_000))
+            .unwrap();
+        assert_eq!(severity, CheckSeverity::Critical);
+        assert!(reason.starts_with("/var:"));
+        assert!(reason.contains("5.0 GiB (5.0%) free"));
+    }
+
+    #[test]
+    fn low_space_absolute_returns_failure() {
+        let check = make_check(Some(Threshold::Absolute(10 << 30)), None);
+        let (_, reason) = check
+            .evaluate_disk_usage(Path::new("/var"), &usage(9 << 30, 500_000))
+            .unwrap();
+        assert!(reason.contains("free, minimum is 10.0 GiB"), "{}", reason);
+    }
+
+    #[test]
+    fn low_inodes_returns_failure() {
+        let check = make_check(None, Some(Threshold::Absolute(1000)));
+        let (_, reason) = check
+            .evaluate_disk_usage(Path::new("/var"), &usage(50 << 30, 999))
+            .unwrap();
+        assert!(reason.contains("999 (0.1%) inodes free, minimum is 1000"));
+    }
+
+    #[test]
+    fn file_systems_without_inodes_are_not_checked() {
+        let check = make_check(None, Some(Threshold::Percent(10.0)));
+        let usage = DiskUsage {
+            free_bytes: 1,
+            total_bytes: 1,
+            free_inodes: 0,
+            total_inodes: 0,
+        };
+        assert!(check
+            .evaluate_disk_usage(Path::new("/var"), &usage)
+            .is_none());
+    }
+
+    #[test]
+    fn warning_thresholds_return_warning() {
+        let check = DiskSpaceCheck {
+            paths: vec![PathBuf::from("/var")],
+            critical_free_space: None,
+            warning_free_space: Some(Threshold::Percent(90.0)),
+            critical_free_inodes: None,
+            warning_free_inodes: Some(Threshold::Percent(90.0)),
+        };
+        let (severity, reason) = check
+            .evaluate_disk_usage(Path::new("/var"), &usage(1, 1))
+            .unwrap();
+        assert_eq!(severity, CheckSeverity::Warning);
+        assert!(reason.contains("warning at 90%"), "{}", reason);
+    }
+
+    #[tokio::test]
+    async fn queries_existing_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let check = DiskSpaceCheck {
+            paths: vec![tmp.path().to_path_buf()],
+            critical_free_space: Some(Threshold::Absolute(0)),
+            warning_free_space: None,
+            critical_free_inodes: None,
+            warning_free_inodes: None,
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn missing_path_returns_error() {
+        let check = make_check(Some(Threshold::Percent(10.0)), None);
+        let check = DiskSpaceCheck {
+            paths: vec![PathBuf::from("/tmp/easycheck_nonexistent_disk_path")],
+            ..check
+        };
+        assert!(check.execute_check().await.is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use crate::options::Options;
+use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+use crate::util::file_watcher::WatchedPath;
+use crate::util::maintenance_info::read_maintenance_file;
+
+/// Drains the service while the drain file exists, so that load balancers stop
+/// sending new connections while existing sessions are continued.
+#[derive(Debug)]
+pub(crate) struct DrainFileCheck {
+    file_path: PathBuf,
+}
+
+#[async_trait]
+impl StatusChecker for DrainFileCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        Ok(options.drain_file_path.as_ref().map(|file_path| Self {
+            file_path: PathBuf::from(file_path),
+        }))
+    }
+
+    fn check_name(&self) -> String {
+        String::from("drain file")
+    }
+
+    fn check_id(&self) -> &str {
+        "drain"
+    }
+
+    fn watched_paths(&self) -> Vec<WatchedPath> {
+        vec![WatchedPath::File(self.file_path.clone())]
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        log::debug!("checking drain file at {:?}", &self.file_path);
+        match read_maintenance_file(&self.file_path).await {
+            // no drain file exists, check is successful
+            Ok(None) => Ok(StatusCheckResult::new_success()),
+            Ok(Some(info)) if info.has_ended() => {
+                log::debug!("ignoring ended drain file {:?}", &self.file_path);
+                Ok(StatusCheckResult::new_success())
+            }
+            Ok(Some(info)) => {
+                let reason = match info.describe() {
+                    Some(description) => format!("drain file exists: {}", description),
+                    None => String::from("drain file exists"),
+                };
+                Ok(StatusCheckResult::new_failure(reason).drain_hold())
+            }
+            Err(error) => {
+                // unable to read the drain file
+                let reason = format!("unable to query drain file existence: {}", error);
+                Ok(StatusCheckResult::new_failure(reason))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_present_returns_drain_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.drain");
+        let check = DrainFileCheck {
+            file_path: file_path.clone(),
+        };
+        assert!(check
+            .execute_check()
+            .await
+            .unwrap()
+            .failure_reason
+            .is_none());
+
+        std::fs::write(&file_path, "reason=deployment\nby=jane").unwrap();
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("drain file exists: deployment (by jane)")
+        );
+        assert!(result.drain);
+        assert!(!result.maintenance);
+    }
+
+    #[tokio::test]
+    async fn ended_file_returns_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.drain");
+        std::fs::write(&file_path, "until=2020-01-01T00:00:00Z").unwrap();
+        let check = DrainFileCheck { file_path };
+        assert!(check
+            .execute_check()
+            .await
+            .unwrap()
+            .failure_reason
+            .is_none());
+    }
+}
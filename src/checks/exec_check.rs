@@ -0,0 +1,523 @@
+use std::ffi::CString;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::options::Options;
+use crate::status::status_checker::{PerformanceData, StatusCheckResult, StatusChecker};
+
+/// The default time a command may run before it gets killed.
+const DEFAULT_EXEC_TIMEOUT_SECONDS: u64 = 10;
+
+/// The maximum number of bytes read from the stdout and stderr of the command each,
+/// further output is discarded.
+const MAX_EXEC_OUTPUT_SIZE: u64 = 64 * 1024;
+
+/// The user and group to run the command as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RunAs {
+    uid: u32,
+    gid: u32,
+}
+
+#[derive(Debug)]
+pub(crate) struct ExecCheck {
+    command: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    working_directory: Option<PathBuf>,
+    timeout: Duration,
+    run_as: Option<RunAs>,
+}
+
+#[async_trait]
+impl StatusChecker for ExecCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        match options.exec_command.to_owned() {
+            None => Ok(None),
+            Some(command) => {
+                let env = options
+                    .exec_env
+                    .iter()
+                    .flatten()
+                    .map(|variable| match variable.split_once('=') {
+                        Some((key, value)) if !key.is_empty() => {
+                            Ok((key.to_string(), value.to_string()))
+                        }
+                        _ => Err(anyhow!(
+                            "exec env {} must have the format KEY=VALUE",
+                            variable
+                        )),
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                let timeout_seconds = options
+                    .exec_timeout_seconds
+                    .unwrap_or(DEFAULT_EXEC_TIMEOUT_SECONDS);
+                if timeout_seconds == 0 {
+                    bail!("exec timeout must be positive");
+                }
+                let run_as = match &options.exec_user {
+                    Some(user) => Some(resolve_user(user)?),
+                    None => None,
+                };
+
+                Ok(Some(Self {
+                    command,
+                    args: options.exec_args.to_owned().unwrap_or_default(),
+                    env,
+                    working_directory: options.exec_working_directory.to_owned(),
+                    timeout: Duration::from_secs(timeout_seconds),
+                    run_as,
+                }))
+            }
+        }
+    }
+
+    fn check_name(&self) -> String {
+        format!("exec check {}", &self.command)
+    }
+
+    fn check_id(&self) -> &str {
+        "exec"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        log::debug!("executing {} {:?}", &self.command, &self.args);
+        let mut command = Command::new(&self.command);
+        command
+            .args(&self.args)
+            .envs(self.env.iter().cloned())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // run the command in its own process group, so that all
+            // processes spawned by it can be killed on timeout
+            .process_group(0)
+            .kill_on_drop(true);
+        if let Some(working_directory) = &self.working_directory {
+            command.current_dir(working_directory);
+        }
+        if let Some(run_as) = &self.run_as {
+            command.uid(run_as.uid).gid(run_as.gid);
+        }
+
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("unable to execute {}", &self.command))?;
+        let process_group_id = child.id();
+        let stdout = child
+            .stdout
+            .take()
+            .context("stdout of command is not piped")?;
+        let stderr = child
+            .stderr
+            .take()
+            .context("stderr of command is not piped")?;
+        let output = timeout(self.timeout, async {
+            tokio::try_join!(read_bounded(stdout), read_bounded(stderr), child.wait())
+        })
+        .await;
+        let (stdout, stderr, exit_status) = match output {
+            Ok(output) => output?,
+            Err(_) => {
+                if let Some(process_group_id) = process_group_id {
+                    // the process group id equals the pid of the process group leader
+                    unsafe { libc::killpg(process_group_id as libc::pid_t, libc::SIGKILL) };
+                }
+                let reason = format!("timeout after {:?}", self.timeout);
+                return Ok(StatusCheckResult::new_failure(reason));
+            }
+        };
+
+        let stderr = String::from_utf8_lossy(&stderr);
+        if !stderr.trim().is_empty() {
+            log::debug!("{} wrote to stderr: {}", &self.command, stderr.trim());
+        }
+        let stdout = String::from_utf8_lossy(&stdout);
+        let (status_text, perfdata) = parse_plugin_output(&stdout);
+        let status_text = if status_text.is_empty() {
+            String::from("no output")
+        } else {
+            status_text
+        };
+
+        let check_result = match exit_status.code() {
+            Some(0) => StatusCheckResult::new_success(),
+            Some(1) => StatusCheckResult::new_warning(format!("WARNING: {}", status_text)),
+            Some(2) => StatusCheckResult::new_failure(format!("CRITICAL: {}", status_text)),
+            Some(3) => StatusCheckResult::new_failure(format!("UNKNOWN: {}", status_text)),
+            Some(code) => StatusCheckResult::new_failure(format!(
+                "UNKNOWN: exited with code {}: {}",
+                code, status_text
+            )),
+            None => StatusCheckResult::new_failure(format!(
+                "UNKNOWN: terminated by signal: {}",
+                status_text
+            )),
+        };
+        Ok(check_result.with_perfdata(perfdata))
+    }
+}
+
+/// Reads the given output of the command up to the maximum output size. The remaining
+/// output is discarded, so that the command is not blocked by a full pipe.
+async fn read_bounded(mut reader: impl AsyncRead + Unpin) -> std::io::Result<Vec<u8>> {
+    let mut output = vec![];
+    (&mut reader)
+        .take(MAX_EXEC_OUTPUT_SIZE)
+        .read_to_end(&mut output)
+        .await?;
+    tokio::io::copy(&mut reader, &mut tokio::io::sink()).await?;
+    Ok(output)
+}
+
+/// Resolves the given user name (or numeric uid) into the uid and primary gid of the user.
+/// A numeric uid without entry in the user database requires an explicit `<uid>:<gid>`.
+fn resolve_user(user: &str) -> anyhow::Result<RunAs> {
+    if let Some((uid, gid)) = user.split_once(':') {
+        let uid = uid.trim().parse().context("uid must be numeric")?;
+        let gid = gid.trim().parse().context("gid must be numeric")?;
+        return Ok(RunAs { uid, gid });
+    }
+
+    let c_user = CString::new(user).context("user name must not contain nul bytes")?;
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let error = unsafe {
+        libc::getpwnam_r(
+            c_user.as_ptr(),
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+    if error != 0 {
+        bail!(
+            "unable to look up user {}: {}",
+            user,
+            std::io::Error::from_raw_os_error(error)
+        );
+    }
+    if result.is_null() {
+        bail!(
+            "user {} does not exist, use <uid>:<gid> for users without entry in the user database",
+            user
+        );
+    }
+
+    Ok(RunAs {
+        uid: passwd.pw_uid,
+        gid: passwd.pw_gid,
+    })
+}
+
+/// Parses the output of a nagios plugin in the format `TEXT | PERFDATA`, optionally
+/// followed by more lines of long text and performance data. Returns the text of the
+/// first line and all performance data.
+fn parse_plugin_output(output: &str) -> (String, Vec<PerformanceData>) {
+    let mut lines = output.lines();
+    let first_line = lines.next().unwrap_or_default();
+    let (status_text, mut perfdata_text) = match first_line.split_once('|') {
+        Some((text, perfdata)) => (text.trim().to_string(), perfdata.to_string()),
+        None => (first_line.trim().to_string(), String::new()),
+    };
+
+    // performance data on following lines starts after the first pipe in the long text
+    let mut in_perfdata = false;
+    for line in lines {
+        if in_perfdata {
+            perfdata_text.push(' ');
+            perfdata_text.push_str(line);
+        } else if let Some((_, perfdata)) = line.split_once('|') {
+            in_perfdata = true;
+            perfdata_text.push(' ');
+            perfdata_text.push_str(perfdata);
+        }
+    }
+
+    (status_text, parse_perfdata(&perfdata_text))
+}
+
+/// Parses nagios performance data in the format `'label'=value[UOM];[warn];[crit];[min];[max]`.
+/// Entries that cannot be parsed are skipped.
+fn parse_perfdata(perfdata: &str) -> Vec<PerformanceData> {
+    split_perfdata_entries(perfdata)
+        .into_iter()
+        .filter_map(|entry| {
+            let parsed = parse_perfdata_entry(&entry);
+            if parsed.is_none() {
+                log::debug!("skipping unparsable performance data {}", entry);
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Splits performance data at whitespace, while respecting single-quoted labels.
+fn split_perfdata_entries(perfdata: &str) -> Vec<String> {
+    let mut entries = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for char in perfdata.chars() {
+        match char {
+            '\'' => {
+                in_quotes = !in_quotes;
+                current.push(char);
+            }
+            char if char.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    entries.push(std::mem::take(&mut current));
+                }
+            }
+            char => current.push(char),
+        }
+    }
+    if !current.is_empty() {
+        entries.push(current);
+    }
+    entries
+}
+
+fn parse_perfdata_entry(entry: &str) -> Option<PerformanceData> {
+    let (label, data) = entry.rsplit_once('=')?;
+    let label = match label.strip_prefix('\'') {
+        Some(quoted) => quoted.strip_suffix('\'')?.replace("''", "'"),
+        None => label.to_string(),
+    };
+    if label.is_empty() {
+        return None;
+    }
+
+    let mut fields = data.split(';');
+    let value_with_unit = fields.next()?;
+    let unit_start = value_with_unit
+        .find(|char: char| !(char.is_ascii_digit() || matches!(char, '.' | '-' | '+' | 'e' | 'E')))
+        .unwrap_or(value_with_unit.len());
+    let value = value_with_unit[..unit_start].parse::<f64>().ok()?;
+    let unit = Some(&value_with_unit[unit_start..]).filter(|unit| !unit.is_empty());
+
+    let mut next_field = || fields.next().filter(|field| !field.is_empty());
+    let warning = next_field().map(str::to_string);
+    let critical = next_field().map(str::to_string);
+    let min = next_field().and_then(|min| min.parse().ok());
+    let max = next_field().and_then(|max| max.parse().ok());
+
+    Some(PerformanceData {
+        label,
+        value,
+        unit: unit.map(str::to_string),
+        warning,
+        critical,
+        min,
+        max,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::status_checker::CheckSeverity;
+    use clap::Parser;
+
+    fn make_check(script: &str) -> ExecCheck {
+        ExecCheck {
+            command: String::from("/bin/sh"),
+            args: vec![String::from("-c"), String::from(script)],
+            env: vec![],
+            working_directory: None,
+            timeout: Duration::from_secs(5),
+            run_as: None,
+        }
+    }
+
+    #[test]
+    fn parses_plugin_output_with_perfdata() {
+        let output = "DISK OK - free space: / 3326 MB (56%) | /=2643MB;5948;5958;0;5968\n";
+        let (status_text, perfdata) = parse_plugin_output(output);
+        assert_eq!(status_text, "DISK OK - free space: / 3326 MB (56%)");
+        assert_eq!(
+            perfdata,
+            vec![PerformanceData {
+                label: String::from("/"),
+                value: 2643.0,
+                unit: Some(String::from("MB")),
+                warning: Some(String::from("5948")),
+                critical: Some(String::from("5958")),
+                min: Some(0.0),
+                max: Some(5968.0),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_multiline_output_and_quoted_labels() {
+        let output =
+            "OK - all fine | time=0.5s;;\nlong text\nmore | 'queue size'=12;100;200\nload=1.5\n";
+        let (status_text, perfdata) = parse_plugin_output(output);
+        assert_eq!(status_text, "OK - all fine");
+        let labels: Vec<_> = perfdata.iter().map(|data| data.label.as_str()).collect();
+        assert_eq!(labels, vec!["time", "queue size", "load"]);
+        assert_eq!(perfdata[0].unit.as_deref(), Some("s"));
+        assert_eq!(perfdata[0].warning, None);
+        assert_eq!(perfdata[1].critical.as_deref(), Some("200"));
+    }
+
+    #[test]
+    fn skips_invalid_perfdata() {
+        assert!(parse_perfdata("garbage =U;1;2 'unclosed=1").is_empty());
+    }
+
+    #[test]
+    fn resolves_numeric_user_without_entry() {
+        let run_as = resolve_user("4242424:4343").unwrap();
+        assert_eq!(run_as.uid, 4242424);
+        assert_eq!(run_as.gid, 4343);
+        assert!(resolve_user("4242424").is_err());
+        assert!(resolve_user("4242424:staff").is_err());
+        assert!(resolve_user("easycheck-nonexistent-user").is_err());
+    }
+
+    #[tokio::test]
+    async fn exit_code_zero_returns_success() {
+        let check = make_check("echo 'OK - fine | time=1ms'; exit 0");
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+        assert_eq!(result.perfdata.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn exit_code_one_returns_warning() {
+        let check = make_check("echo 'WARNING - slow'; exit 1");
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("WARNING: WARNING - slow")
+        );
+        assert_eq!(result.severity, CheckSeverity::Warning);
+    }
+
+    #[tokio::test]
+    async fn exit_code_two_returns_critical_failure() {
+        let check = make_check("echo 'CRIT - queue full | size=500;100;200'; exit 2");
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("CRITICAL: CRIT - queue full")
+        );
+        assert_eq!(result.perfdata[0].label, "size");
+    }
+
+    #[tokio::test]
+    async fn exit_code_three_returns_unknown_failure() {
+        let check = make_check("exit 3");
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(result.failure_reason.as_deref(), Some("UNKNOWN: no output"));
+    }
+
+    #[tokio::test]
+    async fn env_and_working_directory_are_applied() {
+        let directory = tempfile::tempdir().unwrap();
+        let check = ExecCheck {
+            env: vec![(String::from("EASYCHECK_TEST"), String::from("value"))],
+            working_directory: Some(directory.path().to_path_buf()),
+            ..make_check("echo \"$EASYCHECK_TEST $(pwd)\"; exit 2")
+        };
+        let result = check.execute_check().await.unwrap();
+        let expected = format!(
+            "CRITICAL: value {}",
+            directory.path().canonicalize().unwrap().display()
+        );
+        assert_eq!(result.failure_reason.as_deref(), Some(expected.as_str()));
+    }
+
+    #[tokio::test]
+    async fn timeout_kills_process_group() {
+        let directory = tempfile::tempdir().unwrap();
+        let marker = directory.path().join("marker");
+        let script = format!("(sleep 2; touch {}) & sleep 10", marker.display());
+        let check = ExecCheck {
+            timeout: Duration::from_millis(200),
+            ..make_check(&script)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("timeout after 200ms")
+        );
+
+        // the background process must have been killed together with the shell
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert!(!marker.exists());
+    }
+
+    #[tokio::test]
+    async fn output_is_truncated() {
+        let check = make_check("head -c 1000000 /dev/zero | tr '\\0' a; exit 2");
+        let result = check.execute_check().await.unwrap();
+        let failure_reason = result.failure_reason.unwrap();
+        assert!(failure_reason.starts_with("CRITICAL: aaa"));
+        assert_eq!(
+            failure_reason.len(),
+            "CRITICAL: ".len() + MAX_EXEC_OUTPUT_SIZE as usize
+        );
+    }
+
+    #[test]
+    fn env_values_may_contain_commas() {
+        let options = Options::try_parse_from([
+            "easycheck",
+            "--bind",
+            "127.0.0.1:0",
+            "--exec-command",
+            "/bin/true",
+            "--exec-env",
+            "NO_PROXY=localhost,127.0.0.1",
+            "--exec-env",
+            "LANG=C",
+        ])
+        .unwrap();
+        let check = ExecCheck::from_options(&options).unwrap().unwrap();
+        assert_eq!(
+            check.env,
+            vec![
+                (
+                    String::from("NO_PROXY"),
+                    String::from("localhost,127.0.0.1")
+                ),
+                (String::from("LANG"), String::from("C")),
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_timeout_is_rejected() {
+        let options = Options::try_parse_from([
+            "easycheck",
+            "--bind",
+            "127.0.0.1:0",
+            "--exec-command",
+            "/bin/true",
+            "--exec-timeout",
+            "0",
+        ])
+        .unwrap();
+        assert!(ExecCheck::from_options(&options).is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_command_returns_error() {
+        let check = ExecCheck {
+            command: String::from("/nonexistent/easycheck-plugin"),
+            ..make_check("")
+        };
+        assert!(check.execute_check().await.is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use regex::Regex;
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+
+use crate::options::Options;
+use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+
+/// The default maximum amount of bytes that are read from the content file.
+const DEFAULT_MAX_CONTENT_SIZE: u64 = 64 * 1024;
+
+/// The maximum amount of characters of the content that are included in failure reasons.
+const MAX_REASON_CONTENT_LENGTH: usize = 100;
+
+#[derive(Debug)]
+pub(crate) struct FileContentCheck {
+    file_path: PathBuf,
+    max_content_size: u64,
+    /// If present, the content is parsed as json and the value at this pointer is evaluated.
+    json_pointer: Option<String>,
+    expected_content: Option<String>,
+    content_pattern: Option<Regex>,
+}
+
+#[async_trait]
+impl StatusChecker for FileContentCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        match options.content_file_path.to_owned() {
+            None => Ok(None),
+            Some(file_path) => {
+                let content_pattern = match &options.content_pattern {
+                    Some(pattern) => Some(
+                        Regex::new(pattern)
+                            .with_context(|| format!("invalid content pattern {}", pattern))?,
+                    ),
+                    None => None,
+                };
+                if options.content_expected.is_none()
+                    && content_pattern.is_none()
+                    && options.content_json_pointer.is_none()
+                {
+                    bail!("a content file requires an expected value, pattern or json pointer");
+                }
+                if let Some(json_pointer) = &options.content_json_pointer {
+                    if !json_pointer.is_empty() && !json_pointer.starts_with('/') {
+                        bail!("json pointer {} must start with a '/'", json_pointer);
+                    }
+                }
+
+                Ok(Some(Self {
+                    file_path,
+                    max_content_size: options.content_max_size.unwrap_or(DEFAULT_MAX_CONTENT_SIZE),
+                    json_pointer: options.content_json_pointer.to_owned(),
+                    expected_content: options.content_expected.to_owned(),
+                    content_pattern,
+                }))
+            }
+        }
+    }
+
+    fn check_name(&self) -> String {
+        format!("file content check {}", self.file_path.display())
+    }
+
+    fn check_id(&self) -> &str {
+        "content"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        log::debug!("checking content of {:?}", &self.file_path);
+        let content = match self.read_content().await {
+            Ok(content) => content,
+            Err(error) => return Ok(StatusCheckResult::new_failure(format!("{:#}", error))),
+        };
+
+        let content = match &self.json_pointer {
+            Some(json_pointer) => match Self::resolve_json_pointer(&content, json_pointer) {
+                Ok(value) => value,
+                Err(error) => return Ok(StatusCheckResult::new_failure(format!("{:#}", error))),
+            },
+            None => content.trim().to_string(),
+        };
+
+        Ok(self.evaluate_content(&content))
+    }
+}
+
+impl FileContentCheck {
+    /// Reads the content of the file, failing if it is larger than the configured maximum size.
+    async fn read_content(&self) -> anyhow::Result<String> {
+        let file = File::open(&self.file_path)
+            .await
+            .with_context(|| format!("unable to open {}", self.file_path.display()))?;
+
+        // read one more byte than allowed to detect files that exceed the limit
+        let mut content = Vec::new();
+        file.take(self.max_content_size.saturating_add(1))
+            .read_to_end(&mut content)
+            .await
+            .with_context(|| format!("unable to read {}", self.file_path.display()))?;
+        if content.len() as u64 > self.max_content_size {
+            bail!(
+                "{} is larger than {} bytes",
+                self.file_path.display(),
+                self.max_content_size
+            );
+        }
+
+        String::from_utf8(content)
+            .with_context(|| format!("{} does not contain valid utf-8", self.file_path.display()))
+    }
+
+    /// Parses the content as json and returns the value at the given pointer. Strings are
+    /// returned without quotes, all other values in their json representation.
+    fn resolve_json_pointer(content: &str, json_pointer: &str) -> anyhow::Result<String> {
+        let document: serde_json::Value =
+            serde_json::from_str(content).context("content is not valid json")?;
+        match document.pointer(json_pointer) {
+            None | Some(serde_json::Value::Null) => {
+                bail!("json pointer {} does not resolve to a value", json_pointer)
+            }
+            Some(serde_json::Value::String(value)) => Ok(value.clone()),
+            Some(value) => Ok(value.to_string()),
+        }
+    }
+
+    /// Evaluates the given content against the configured expected value and pattern.
+    fn evaluate_content(&self, content: &str) -> StatusCheckResult {
+        if let Some(expected_content) = &self.expected_content {
+            if content != expected_content {
+                return StatusCheckResult::new_failure(format!(
+                    "content is {:?}, expected {:?}",
+                    truncate_content(content),
+                    expected_content
+                ));
+            }
+        }
+        if let Some(content_pattern) = &self.content_pattern {
+            if !content_pattern.is_match(content) {
+                return StatusCheckResult::new_failure(format!(
+                    "content {:?} does not match {}",
+                    truncate_content(content),
+                    content_pattern
+                ));
+            }
+        }
+
+        StatusCheckResult::new_success()
+    }
+}
+
+/// Shortens the given content to be included in a failure reason.
+fn truncate_content(content: &str) -> String {
+    match content.char_indices().nth(MAX_REASON_CONTENT_LENGTH) {
+        Some((index, _)) => format!("{}...", &content[..index]),
+        None => content.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn fixture_file(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    fn make_check(file: &NamedTempFile) -> FileContentCheck {
+        FileContentCheck {
+            file_path: file.path().to_path_buf(),
+            max_content_size: DEFAULT_MAX_CONTENT_SIZE,
+            json_pointer: None,
+            expected_content: None,
+            content_pattern: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn expected_literal_matches() {
+        let file = fixture_file("READY\n");
+        let check = FileContentCheck {
+            expected_content: Some("READY".to_string()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn unexpected_literal_returns_failure_with_content() {
+        let file = fixture_file("DEPLOYING\n");
+        let check = FileContentCheck {
+            expected_content: Some("READY".to_string()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("content is \"DEPLOYING\", expected \"READY\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn pattern_is_evaluated() {
+        let file = fixture_file("wsrep_local_state_comment = Synced\nwsrep_ready = ON\n");
+        let check = FileContentCheck {
+            content_pattern: Some(Regex::new(r"wsrep_local_state_comment\s*=\s*Synced").unwrap()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+
+        let check = FileContentCheck {
+            content_pattern: Some(Regex::new(r"wsrep_ready = OFF").unwrap()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("does not match"));
+    }
+
+    #[tokio::test]
+    async fn json_pointer_value_is_compared() {
+        let file = fixture_file(r#"{"deploy": {"state": "READY", "release": 42}}"#);
+        let check = FileContentCheck {
+            json_pointer: Some("/deploy/state".to_string()),
+            expected_content: Some("READY".to_string()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+
+        let check = FileContentCheck {
+            json_pointer: Some("/deploy/release".to_string()),
+            expected_content: Some("41".to_string()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("content is \"42\", expected \"41\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_json_pointer_returns_failure() {
+        let file = fixture_file(r#"{"deploy": {}}"#);
+        let check = FileContentCheck {
+            json_pointer: Some("/deploy/state".to_string()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("does not resolve"));
+    }
+
+    #[tokio::test]
+    async fn oversized_file_returns_failure() {
+        let file = fixture_file("READY");
+        let check = FileContentCheck {
+            max_content_size: 4,
+            expected_content: Some("READY".to_string()),
+            ..make_check(&file)
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result
+            .failure_reason
+            .unwrap()
+            .contains("larger than 4 bytes"));
+    }
+
+    #[tokio::test]
+    async fn missing_file_returns_failure() {
+        let check = FileContentCheck {
+            file_path: PathBuf::from("/tmp/easycheck_nonexistent_content_file"),
+            max_content_size: DEFAULT_MAX_CONTENT_SIZE,
+            json_pointer: None,
+            expected_content: Some("READY".to_string()),
+            content_pattern: None,
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("unable to open"));
+    }
+
+    #[test]
+    fn long_content_is_truncated() {
+        let content = "x".repeat(150);
+        let truncated = truncate_content(&content);
+        assert_eq!(truncated.len(), MAX_REASON_CONTENT_LENGTH + 3);
+        assert!(truncated.ends_with("..."));
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use glob::Pattern;
+use tokio::fs;
+
+use crate::options::Options;
+use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+
+#[derive(Debug)]
+pub(crate) struct FileFreshnessCheck {
+    /// The path of the heartbeat file, or a glob pattern of which the newest match is used.
+    file_pattern: String,
+    max_age: Duration,
+    fail_if_missing: bool,
+}
+
+#[async_trait]
+impl StatusChecker for FileFreshnessCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        match options.heartbeat_file_pattern.to_owned() {
+            None => Ok(None),
+            Some(file_pattern) => {
+                Pattern::new(&file_pattern)
+                    .with_context(|| format!("invalid heartbeat file pattern {}", file_pattern))?;
+                let Some(max_age_seconds) = options.heartbeat_max_age_seconds else {
+                    bail!("a heartbeat max age is required when a heartbeat file is configured");
+                };
+                Ok(Some(Self {
+                    file_pattern,
+                    max_age: Duration::from_secs(max_age_seconds),
+                    fail_if_missing: options.heartbeat_fail_if_missing.unwrap_or(false),
+                }))
+            }
+        }
+    }
+
+    fn check_name(&self) -> String {
+        format!("file freshness check {}", &self.file_pattern)
+    }
+
+    fn check_id(&self) -> &str {
+        "heartbeat"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        log::debug!("checking freshness of {}", &self.file_pattern);
+        let mut newest_modification: Option<(PathBuf, SystemTime)> = None;
+        let file_pattern = self.file_pattern.clone();
+        let file_paths =
+            tokio::task::spawn_blocking(move || Self::resolve_file_paths(&file_pattern)).await??;
+        for file_path in file_paths {
+            let modified = match fs::metadata(&file_path).await {
+                Ok(metadata) => metadata.modified()?,
+                // the file was removed between listing and reading the metadata
+                Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                Err(error) => {
+                    let reason = format!("unable to query {}: {}", file_path.display(), error);
+                    return Ok(StatusCheckResult::new_failure(reason));
+                }
+            };
+            let is_newer = match &newest_modification {
+                Some((_, newest)) => modified > *newest,
+                None => true,
+            };
+            if is_newer {
+                newest_modification = Some((file_path, modified));
+            }
+        }
+
+        let Some((file_path, modified)) = newest_modification else {
+            return if self.fail_if_missing {
+                let reason = format!("no file matching {} exists", &self.file_pattern);
+                Ok(StatusCheckResult::new_failure(reason))
+            } else {
+                Ok(StatusCheckResult::new_success())
+            };
+        };
+
+        // modification times in the future are treated as just modified
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or(Duration::ZERO);
+        if age > self.max_age {
+            let reason = format!(
+                "{} was last modified {}s ago, maximum age is {}s",
+                file_path.display(),
+                age.as_secs(),
+                self.max_age.as_secs()
+            );
+            Ok(StatusCheckResult::new_failure(reason))
+        } else {
+            Ok(StatusCheckResult::new_success())
+        }
+    }
+}
+
+impl FileFreshnessCheck {
+    /// Resolves the given file pattern into the existing file paths.
+    fn resolve_file_paths(file_pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
+        let paths = glob::glob(file_pattern)?
+            .filter_map(|entry| match entry {
+                Ok(path) => Some(path),
+                Err(error) => {
+                    log::debug!("unable to read heartbeat file candidate: {}", error);
+                    None
+                }
+            })
+            .collect();
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    fn make_check(
+        file_pattern: String,
+        max_age: Duration,
+        fail_if_missing: bool,
+    ) -> FileFreshnessCheck {
+        FileFreshnessCheck {
+            file_pattern,
+            max_age,
+            fail_if_missing,
+        }
+    }
+
+    fn touch(directory: &TempDir, name: &str, age: Duration) -> PathBuf {
+        let path = directory.path().join(name);
+        let file = File::create(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn fresh_file_returns_success() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = touch(&directory, "heartbeat", Duration::from_secs(10));
+        let check = make_check(path.display().to_string(), Duration::from_secs(60), true);
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn stale_file_returns_failure() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = touch(&directory, "heartbeat", Duration::from_secs(600));
+        let check = make_check(path.display().to_string(), Duration::from_secs(60), true);
+        let result = check.execute_check().await.unwrap();
+        let reason = result.failure_reason.unwrap();
+        assert!(reason.contains("maximum age is 60s"), "{}", reason);
+    }
+
+    #[tokio::test]
+    async fn missing_file_only_fails_if_enabled() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("heartbeat").display().to_string();
+
+        let check = make_check(path.clone(), Duration::from_secs(60), false);
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+
+        let check = make_check(path, Duration::from_secs(60), true);
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("no file matching"));
+    }
+
+    #[tokio::test]
+    async fn glob_uses_newest_match() {
+        let directory = tempfile::tempdir().unwrap();
+        touch(&directory, "backup-1.done", Duration::from_secs(600));
+        touch(&directory, "backup-2.done", Duration::from_secs(10));
+        let pattern = directory.path().join("backup-*.done").display().to_string();
+
+        let check = make_check(pattern.clone(), Duration::from_secs(60), true);
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+
+        let check = make_check(pattern, Duration::from_secs(5), true);
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("backup-2.done"));
+    }
+}
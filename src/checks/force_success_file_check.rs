@@ -1,14 +1,72 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
-use tokio::fs;
 
 use crate::options::Options;
 use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+use crate::util::file_watcher::WatchedPath;
+use crate::util::maintenance_info::{list_drop_in_files, read_maintenance_file};
+
+/// The path of the force success file, if none is configured.
+pub(crate) const DEFAULT_FORCE_SUCCESS_FILE_PATH: &str = "easycheck.success";
 
 #[derive(Debug)]
 pub(crate) struct ForceSuccessFileCheck {
     file_path: PathBuf,
+    /// The directory in which each file forces success, if configured.
+    dir_path: Option<PathBuf>,
+}
+
+impl ForceSuccessFileCheck {
+    /// Checks if the force success file or any file in the force success directory exists.
+    /// Files whose `until` timestamp passed are ignored.
+    async fn any_file_exists(&self) -> bool {
+        if Self::is_active(&self.file_path).await {
+            return true;
+        }
+        let Some(dir_path) = &self.dir_path else {
+            return false;
+        };
+        match list_drop_in_files(dir_path).await {
+            Ok(file_paths) => {
+                for file_path in file_paths {
+                    if Self::is_active(&file_path).await {
+                        return true;
+                    }
+                }
+                false
+            }
+            Err(error) => {
+                log::warn!(
+                    "unable to list force success directory {:?}: {}",
+                    dir_path,
+                    error
+                );
+                false
+            }
+        }
+    }
+
+    /// Checks if the given force success file exists and did not end yet.
+    async fn is_active(file_path: &Path) -> bool {
+        match read_maintenance_file(file_path).await {
+            Ok(Some(info)) if info.has_ended() => {
+                log::debug!("ignoring ended force success file {:?}", file_path);
+                false
+            }
+            Ok(info) => info.is_some(),
+            // the file may exist, but cannot be read: fail open, as a force success
+            // file is meant to keep the service available in exactly such situations
+            Err(error) => {
+                log::warn!(
+                    "unable to read force success file {:?}, assuming it is present: {}",
+                    file_path,
+                    error
+                );
+                true
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -17,26 +75,41 @@ impl StatusChecker for ForceSuccessFileCheck {
         let force_success_file_path = options
             .force_success_file_path
             .to_owned()
-            .unwrap_or_else(|| String::from("easycheck.success"));
+            .unwrap_or_else(|| String::from(DEFAULT_FORCE_SUCCESS_FILE_PATH));
         let file_path = PathBuf::from(force_success_file_path);
-        Ok(Some(Self { file_path }))
+        let dir_path = options.force_success_dir_path.as_ref().map(PathBuf::from);
+        Ok(Some(Self {
+            file_path,
+            dir_path,
+        }))
     }
 
     fn check_name(&self) -> String {
         String::from("force success file")
     }
 
+    fn check_id(&self) -> &str {
+        "force-success"
+    }
+
+    fn watched_paths(&self) -> Vec<WatchedPath> {
+        let mut watched_paths = vec![WatchedPath::File(self.file_path.clone())];
+        watched_paths.extend(self.dir_path.clone().map(WatchedPath::Directory));
+        watched_paths
+    }
+
     async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
-        log::debug!("checking force success file at {:?}", &self.file_path);
-        match fs::metadata(&self.file_path).await {
-            Ok(_) => {
-                let check_result = StatusCheckResult::new_success().ignore_other_results();
-                Ok(check_result)
-            }
-            Err(_) => {
-                let check_result = StatusCheckResult::new_success();
-                Ok(check_result)
-            }
+        log::debug!(
+            "checking force success file at {:?} and directory {:?}",
+            &self.file_path,
+            &self.dir_path
+        );
+        if self.any_file_exists().await {
+            let check_result = StatusCheckResult::new_success().ignore_other_results();
+            Ok(check_result)
+        } else {
+            let check_result = StatusCheckResult::new_success();
+            Ok(check_result)
         }
     }
 }
@@ -51,6 +124,7 @@ mod tests {
         let tmp = NamedTempFile::new().unwrap();
         let check = ForceSuccessFileCheck {
             file_path: tmp.path().to_path_buf(),
+            dir_path: None,
         };
         let result = check.execute_check().await.unwrap();
         assert!(result.failure_reason.is_none());
@@ -61,9 +135,46 @@ mod tests {
     async fn file_absent_returns_success_without_ignore() {
         let check = ForceSuccessFileCheck {
             file_path: PathBuf::from("/tmp/easycheck_nonexistent_force_success_test"),
+            dir_path: None,
         };
         let result = check.execute_check().await.unwrap();
         assert!(result.failure_reason.is_none());
         assert!(!result.ignore_other_results);
     }
+
+    #[tokio::test]
+    async fn file_in_dir_returns_success_with_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = ForceSuccessFileCheck {
+            file_path: dir.path().join("easycheck.success"),
+            dir_path: Some(dir.path().join("force-success.d")),
+        };
+        assert!(!check.execute_check().await.unwrap().ignore_other_results);
+
+        std::fs::create_dir(dir.path().join("force-success.d")).unwrap();
+        std::fs::write(dir.path().join("force-success.d").join("on-call"), "").unwrap();
+        assert!(check.execute_check().await.unwrap().ignore_other_results);
+    }
+
+    #[tokio::test]
+    async fn unreadable_file_returns_success_with_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = ForceSuccessFileCheck {
+            file_path: dir.path().to_path_buf(),
+            dir_path: None,
+        };
+        assert!(check.execute_check().await.unwrap().ignore_other_results);
+    }
+
+    #[tokio::test]
+    async fn ended_file_returns_success_without_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.success");
+        std::fs::write(&file_path, "until=2020-01-01T00:00:00Z").unwrap();
+        let check = ForceSuccessFileCheck {
+            file_path,
+            dir_path: None,
+        };
+        assert!(!check.execute_check().await.unwrap().ignore_other_results);
+    }
 }
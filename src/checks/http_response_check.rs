@@ -1,7 +1,8 @@
-use crate::options::{Options, ProxyProtocolVersion};
+use crate::options::Options;
 use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+use crate::util::proxy_protocol::{encode_proxy_header, ProxyProtocolSettings};
 use crate::util::tcp_connector::{RealTcpConnector, TcpConnector};
-use anyhow::Context;
+use anyhow::{bail, Context};
 use async_trait::async_trait;
 use http_body_util::Empty;
 use hyper::body::Bytes;
@@ -9,7 +10,6 @@ use hyper::client::conn::http1::handshake;
 use hyper::header::HOST;
 use hyper::{Method, Request, StatusCode, Uri};
 use hyper_util::rt::TokioIo;
-use proxy_header::{ProxiedAddress, ProxyHeader};
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::time::Duration;
@@ -17,84 +17,54 @@ use tokio::io::AsyncWriteExt;
 use tokio::time::timeout;
 
 pub(crate) struct HttpResponseCheck {
+    check_id: String,
     remote_addr: SocketAddr,
     host_header_value: String,
     endpoint: Uri,
     request_line_target: String,
     http_method: Method,
     up_status_codes: Vec<StatusCode>,
-    proxy_protocol_version: Option<ProxyProtocolVersion>,
+    proxy_protocol_header: Option<Vec<u8>>,
     connector: Box<dyn TcpConnector>,
 }
 
-fn encode_proxy_header(version: &ProxyProtocolVersion) -> anyhow::Result<Vec<u8>> {
-    let local_addr = SocketAddr::from_str("127.0.0.1:80")?;
-    let local_address = ProxiedAddress::stream(local_addr, local_addr);
-    let proxy_header = ProxyHeader::with_address(local_address);
-
-    let mut buffer = Vec::<u8>::new();
-    match version {
-        ProxyProtocolVersion::V1 => proxy_header.encode_v1(&mut buffer)?,
-        ProxyProtocolVersion::V2 => proxy_header.encode_v2(&mut buffer)?,
-    }
-    Ok(buffer)
-}
-
 #[async_trait]
 impl StatusChecker for HttpResponseCheck {
     fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
-        match options.http_check_url.to_owned() {
-            None => Ok(None),
-            Some(endpoint) => {
-                let authority = endpoint.authority().context("invalid http check url")?;
-                let remote_host = authority.host();
-                let remote_port = authority.port().map(|port| port.as_u16()).unwrap_or(80);
-                let remote_host = format!("{}:{}", remote_host, remote_port);
-                let remote_addr = SocketAddr::from_str(&remote_host)
-                    .context("http check url must contain a ip address")?;
-
-                let host_header_value = authority.as_str().to_string();
-                let http_method = options.http_check_method.to_owned().unwrap_or(Method::GET);
-                let up_status_codes = options
-                    .http_check_response_codes
-                    .to_owned()
-                    .unwrap_or(vec![StatusCode::OK]);
-                let proxy_protocol_version = options.http_proxy_protocol_version.clone();
-
-                // extracts the path and query part of the uri to use for the request line
-                // GET <request_line_target> ...
-                // this must start with a '/', therefore the extra logic in the mapping step,
-                // as PathAndQuery.as_str() does not return a leading / if only the query part exists
-                let request_line_target = endpoint
-                    .path_and_query()
-                    .map(|pq| {
-                        let pg_str = pq.as_str();
-                        if pg_str.starts_with('/') {
-                            pg_str.to_string()
-                        } else {
-                            format!("/{}", pg_str)
-                        }
-                    })
-                    .unwrap_or_else(|| "/".to_string());
-
-                Ok(Some(Self {
-                    remote_addr,
-                    host_header_value,
-                    endpoint,
-                    request_line_target,
-                    http_method,
-                    up_status_codes,
-                    proxy_protocol_version,
-                    connector: Box::new(RealTcpConnector),
-                }))
-            }
+        // multiple urls result in multiple checks, which are only constructed by all_from_options
+        let mut checks = Self::all_from_options(options)?;
+        if checks.len() > 1 {
+            bail!("multiple http urls are configured");
         }
+        Ok(checks.pop())
+    }
+
+    fn all_from_options(options: &Options) -> anyhow::Result<Vec<Self>> {
+        let endpoints = options.http_check_url.to_owned().unwrap_or_default();
+        let multiple_endpoints = endpoints.len() > 1;
+        endpoints
+            .into_iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                // the checks are only distinguished by index if multiple urls are given
+                let check_id = if multiple_endpoints {
+                    format!("http.{}", index + 1)
+                } else {
+                    String::from("http")
+                };
+                Self::from_endpoint(options, endpoint, check_id)
+            })
+            .collect()
     }
 
     fn check_name(&self) -> String {
         format!("http endpoint check {}", &self.endpoint)
     }
 
+    fn check_id(&self) -> &str {
+        &self.check_id
+    }
+
     async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
         log::debug!(
             "checking http endpoint {} ({} {})",
@@ -104,9 +74,8 @@ impl StatusChecker for HttpResponseCheck {
         );
         let response_code = timeout(Duration::from_secs(5), async {
             let mut remote_stream = self.connector.connect(&self.remote_addr).await?;
-            if let Some(proxy_protocol_version) = &self.proxy_protocol_version {
-                let proxy_protocol_data = encode_proxy_header(proxy_protocol_version)?;
-                remote_stream.write_all(&proxy_protocol_data).await?;
+            if let Some(proxy_protocol_header) = &self.proxy_protocol_header {
+                remote_stream.write_all(proxy_protocol_header).await?;
             }
 
             let (mut sender, connection) = handshake(TokioIo::new(remote_stream)).await?;
@@ -127,6 +96,58 @@ impl StatusChecker for HttpResponseCheck {
 }
 
 impl HttpResponseCheck {
+    /// Constructs the check of the given http endpoint, using the shared http options.
+    fn from_endpoint(options: &Options, endpoint: Uri, check_id: String) -> anyhow::Result<Self> {
+        let authority = endpoint.authority().context("invalid http check url")?;
+        let remote_host = authority.host();
+        let remote_port = authority.port().map(|port| port.as_u16()).unwrap_or(80);
+        let remote_host = format!("{}:{}", remote_host, remote_port);
+        let remote_addr = SocketAddr::from_str(&remote_host)
+            .context("http check url must contain a ip address")?;
+
+        let host_header_value = authority.as_str().to_string();
+        let http_method = options.http_check_method.to_owned().unwrap_or(Method::GET);
+        let up_status_codes = options
+            .http_check_response_codes
+            .to_owned()
+            .unwrap_or(vec![StatusCode::OK]);
+        let proxy_protocol_header = match &options.http_proxy_protocol_version {
+            Some(version) => {
+                let settings = ProxyProtocolSettings::from_options(options)?;
+                Some(encode_proxy_header(version, &settings)?)
+            }
+            None => None,
+        };
+
+        // extracts the path and query part of the uri to use for the request line
+        // GET <request_line_target> ...
+        // this must start with a '/', therefore the extra logic in the mapping step,
+        // as PathAndQuery.as_str() does not return a leading / if only the query part exists
+        let request_line_target = endpoint
+            .path_and_query()
+            .map(|pq| {
+                let pg_str = pq.as_str();
+                if pg_str.starts_with('/') {
+                    pg_str.to_string()
+                } else {
+                    format!("/{}", pg_str)
+                }
+            })
+            .unwrap_or_else(|| "/".to_string());
+
+        Ok(Self {
+            check_id,
+            remote_addr,
+            host_header_value,
+            endpoint,
+            request_line_target,
+            http_method,
+            up_status_codes,
+            proxy_protocol_header,
+            connector: Box::new(RealTcpConnector),
+        })
+    }
+
     fn evaluate_response_code(&self, response_code: StatusCode) -> StatusCheckResult {
         if self.up_status_codes.contains(&response_code) {
             StatusCheckResult::new_success()
@@ -139,7 +160,9 @@ impl HttpResponseCheck {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::ProxyProtocolVersion;
     use crate::util::tcp_connector::AsyncStream;
+    use clap::Parser;
     use std::io;
     use std::pin::Pin;
 
@@ -187,13 +210,16 @@ mod tests {
         connector: Box<dyn TcpConnector>,
     ) -> HttpResponseCheck {
         HttpResponseCheck {
+            check_id: String::from("http"),
             remote_addr: dummy_addr(),
             host_header_value: dummy_addr().to_string(),
             endpoint: format!("http://{}/health", dummy_addr()).parse().unwrap(),
             request_line_target: "/health".to_string(),
             http_method: Method::GET,
             up_status_codes,
-            proxy_protocol_version,
+            proxy_protocol_header: proxy_protocol_version.map(|version| {
+                encode_proxy_header(&version, &ProxyProtocolSettings::default()).unwrap()
+            }),
             connector,
         }
     }
@@ -327,7 +353,9 @@ mod tests {
 
     #[tokio::test]
     async fn http_request_with_proxy_protocol_v1() {
-        let expected_header = encode_proxy_header(&ProxyProtocolVersion::V1).unwrap();
+        let expected_header =
+            encode_proxy_header(&ProxyProtocolVersion::V1, &ProxyProtocolSettings::default())
+                .unwrap();
         let prefix_len = expected_header.len();
 
         let (client_stream, server_stream) = tokio::io::duplex(8192);
@@ -351,7 +379,9 @@ mod tests {
 
     #[tokio::test]
     async fn http_request_with_proxy_protocol_v2() {
-        let expected_header = encode_proxy_header(&ProxyProtocolVersion::V2).unwrap();
+        let expected_header =
+            encode_proxy_header(&ProxyProtocolVersion::V2, &ProxyProtocolSettings::default())
+                .unwrap();
         let prefix_len = expected_header.len();
 
         let (client_stream, server_stream) = tokio::io::duplex(8192);
@@ -385,4 +415,36 @@ mod tests {
         let result = check.execute_check().await;
         assert!(result.is_err());
     }
+
+    #[test]
+    fn urls_with_commas_in_query_are_not_split() {
+        let options = Options::try_parse_from([
+            "easycheck",
+            "--bind",
+            "127.0.0.1:0",
+            "--http-url",
+            "http://127.0.0.1:1/health?checks=db,redis",
+        ])
+        .unwrap();
+        let check = HttpResponseCheck::from_options(&options).unwrap().unwrap();
+        assert_eq!(check.check_id(), "http");
+        assert_eq!(check.request_line_target, "/health?checks=db,redis");
+    }
+
+    #[test]
+    fn repeated_urls_are_checked_separately() {
+        let options = Options::try_parse_from([
+            "easycheck",
+            "--bind",
+            "127.0.0.1:0",
+            "--http-url",
+            "http://127.0.0.1:1/",
+            "--http-url",
+            "http://127.0.0.1:2/",
+        ])
+        .unwrap();
+        let checks = HttpResponseCheck::all_from_options(&options).unwrap();
+        let check_ids: Vec<_> = checks.iter().map(|check| check.check_id()).collect();
+        assert_eq!(check_ids, vec!["http.1", "http.2"]);
+    }
 }
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::fs;
+use tokio::time::Instant;
+
+use crate::options::Options;
+use crate::status::status_checker::{CheckSeverity, StatusCheckResult, StatusChecker};
+use crate::util::threshold::{format_bytes, percent_of, Threshold};
+
+/// The memory and swap values read from the meminfo file, in bytes.
+#[derive(Debug, Clone, PartialEq)]
+struct MemInfo {
+    mem_total: u64,
+    mem_available: u64,
+    swap_total: u64,
+    swap_free: u64,
+}
+
+/// The amount of pages swapped in since boot, sampled at a specific time.
+#[derive(Debug, Clone, Copy)]
+struct SwapInSample {
+    timestamp: Instant,
+    pages_swapped_in: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct MemoryCheck {
+    meminfo_path: PathBuf,
+    vmstat_path: PathBuf,
+    critical_available_memory: Option<Threshold>,
+    warning_available_memory: Option<Threshold>,
+    critical_swap_usage: Option<Threshold>,
+    warning_swap_usage: Option<Threshold>,
+    critical_swap_in_rate: Option<f64>,
+    warning_swap_in_rate: Option<f64>,
+    /// The swap-in sample of the previous check cycle, used to calculate the swap-in rate.
+    last_swap_in_sample: Mutex<Option<SwapInSample>>,
+}
+
+#[async_trait]
+impl StatusChecker for MemoryCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        let check = Self {
+            meminfo_path: options
+                .meminfo_path
+                .to_owned()
+                .unwrap_or_else(|| PathBuf::from("/proc/meminfo")),
+            vmstat_path: options
+                .vmstat_path
+                .to_owned()
+                .unwrap_or_else(|| PathBuf::from("/proc/vmstat")),
+            critical_available_memory: options.memory_min_available,
+            warning_available_memory: options.memory_warn_available,
+            critical_swap_usage: options.swap_max_used,
+            warning_swap_usage: options.swap_warn_used,
+            critical_swap_in_rate: options.swap_max_in_rate,
+            warning_swap_in_rate: options.swap_warn_in_rate,
+            last_swap_in_sample: Mutex::new(None),
+        };
+
+        // the check is only enabled if at least one threshold is configured
+        let enabled = check.critical_available_memory.is_some()
+            || check.warning_available_memory.is_some()
+            || check.critical_swap_usage.is_some()
+            || check.warning_swap_usage.is_some()
+            || check.critical_swap_in_rate.is_some()
+            || check.warning_swap_in_rate.is_some();
+        Ok(enabled.then_some(check))
+    }
+
+    fn check_name(&self) -> String {
+        String::from("memory check")
+    }
+
+    fn check_id(&self) -> &str {
+        "memory"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        log::debug!("checking memory usage from {:?}", &self.meminfo_path);
+        let meminfo_content = fs::read_to_string(&self.meminfo_path)
+            .await
+            .with_context(|| format!("unable to read {}", self.meminfo_path.display()))?;
+        let meminfo = parse_meminfo(&meminfo_content)?;
+
+        let swap_in_rate =
+            if self.critical_swap_in_rate.is_some() || self.warning_swap_in_rate.is_some() {
+                let vmstat_content = fs::read_to_string(&self.vmstat_path)
+                    .await
+                    .with_context(|| format!("unable to read {}", self.vmstat_path.display()))?;
+                let pages_swapped_in = parse_key_value_file(&vmstat_content)
+                    .get("pswpin")
+                    .copied()
+                    .context("pswpin is missing in vmstat")?;
+                self.update_swap_in_rate(SwapInSample {
+                    timestamp: Instant::now(),
+                    pages_swapped_in,
+                })
+            } else {
+                None
+            };
+
+        match self.evaluate(&meminfo, swap_in_rate) {
+            Some((severity, reason)) => Ok(StatusCheckResult::new_with_severity(severity, reason)),
+            None => Ok(StatusCheckResult::new_success()),
+        }
+    }
+}
+
+impl MemoryCheck {
+    /// Stores the given swap-in sample and returns the swap-in rate in pages per
+    /// second since the last sample. Returns None if there is no previous sample.
+    fn update_swap_in_rate(&self, sample: SwapInSample) -> Option<f64> {
+        let mut last_sample = self.last_swap_in_sample.lock().unwrap();
+        let previous_sample = last_sample.replace(sample)?;
+        let elapsed_seconds = sample
+            .timestamp
+            .duration_since(previous_sample.timestamp)
+            .as_secs_f64();
+        if elapsed_seconds <= 0.0 {
+            return None;
+        }
+
+        let swapped_in = sample
+            .pages_swapped_in
+            .saturating_sub(previous_sample.pages_swapped_in);
+        Some(swapped_in as f64 / elapsed_seconds)
+    }
+
+    /// Evaluates the given memory values against the configured thresholds. Returns the
+    /// severity and reason if a critical or warning threshold is crossed.
+    fn evaluate(
+        &self,
+        meminfo: &MemInfo,
+        swap_in_rate: Option<f64>,
+    ) -> Option<(CheckSeverity, String)> {
+        let available_memory = format!(
+            "{} ({:.1}%) memory available",
+            format_bytes(meminfo.mem_available),
+            percent_of(meminfo.mem_available, meminfo.mem_total)
+        );
+        let swap_used = meminfo.swap_total.saturating_sub(meminfo.swap_free);
+        let used_swap = format!(
+            "{} ({:.1}%) swap used",
+            format_bytes(swap_used),
+            percent_of(swap_used, meminfo.swap_total)
+        );
+        // swap usage can only be evaluated if swap is enabled on the system
+        let has_swap = meminfo.swap_total > 0;
+
+        let mut critical_reasons = vec![];
+        if let Some(threshold) = &self.critical_available_memory {
+            if threshold.is_undercut_by(meminfo.mem_available, meminfo.mem_total) {
+                critical_reasons.push(format!(
+                    "{}, minimum is {}",
+                    available_memory,
+                    threshold.format_as_bytes()
+                ));
+            }
+        }
+        if let Some(threshold) = &self.critical_swap_usage {
+            if has_swap && threshold.is_exceeded_by(swap_used, meminfo.swap_total) {
+                critical_reasons.push(format!(
+                    "{}, maximum is {}",
+                    used_swap,
+                    threshold.format_as_bytes()
+                ));
+            }
+        }
+        if let (Some(max_rate), Some(rate)) = (self.critical_swap_in_rate, swap_in_rate) {
+            if rate > max_rate {
+                critical_reasons.push(format!(
+                    "swapping in {:.1} pages/s, maximum is {}",
+                    rate, max_rate
+                ));
+            }
+        }
+        if !critical_reasons.is_empty() {
+            return Some((CheckSeverity::Critical, critical_reasons.join(", ")));
+        }
+
+        let mut warning_reasons = vec![];
+        if let Some(threshold) = &self.warning_available_memory {
+            if threshold.is_undercut_by(meminfo.mem_available, meminfo.mem_total) {
+                warning_reasons.push(format!(
+                    "{}, warning at {}",
+                    available_memory,
+                    threshold.format_as_bytes()
+                ));
+            }
+        }
+        if let Some(threshold) = &self.warning_swap_usage {
+            if has_swap && threshold.is_exceeded_by(swap_used, meminfo.swap_total) {
+                warning_reasons.push(format!(
+                    "{}, warning at {}",
+                    used_swap,
+                    threshold.format_as_bytes()
+                ));
+            }
+        }
+        if let (Some(max_rate), Some(rate)) = (self.warning_swap_in_rate, swap_in_rate) {
+            if rate > max_rate {
+                warning_reasons.push(format!(
+                    "swapping in {:.1} pages/s, warning at {}",
+                    rate, max_rate
+                ));
+            }
+        }
+        if !warning_reasons.is_empty() {
+            return Some((CheckSeverity::Warning, warning_reasons.join(", ")));
+        }
+        None
+    }
+}
+
+/// Parses a file in the format used by /proc/meminfo and /proc/vmstat, one
+/// `<key>[:] <value>` pair per line. Lines that cannot be parsed are skipped.
+fn parse_key_value_file(content: &str) -> HashMap<&str, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let key = parts.next()?.trim_end_matches(':');
+            let value = parts.next()?.parse::<u64>().ok()?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Parses the relevant memory values from the content of /proc/meminfo.
+/// All values in the file are given in kibibytes.
+fn parse_meminfo(content: &str) -> anyhow::Result<MemInfo> {
+    let values = parse_key_value_file(content);
+    let read_bytes = |key: &str| {
+        values
+            .get(key)
+            .map(|kib| kib.saturating_mul(1024))
+            .with_context(|| format!("{} is missing in meminfo", key))
+    };
+
+    Ok(MemInfo {
+        mem_total: read_bytes("MemTotal")?,
+        mem_available: read_bytes("MemAvailable")?,
+        swap_total: read_bytes("SwapTotal").unwrap_or(0),
+        swap_free: read_bytes("SwapFree").unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::Duration;
+    use tempfile::NamedTempFile;
+
+    const MEMINFO_FIXTURE: &str = "MemTotal:       16000000 kB
+MemFree:         1000000 kB
+MemAvailable:    4000000 kB
+Buffers:          200000 kB
+SwapTotal:       2000000 kB
+SwapFree:        1500000 kB
+";
+
+    fn make_check(meminfo_path: PathBuf) -> MemoryCheck {
+        MemoryCheck {
+            meminfo_path,
+            vmstat_path: PathBuf::from("/tmp/easycheck_nonexistent_vmstat"),
+            critical_available_memory: None,
+            warning_available_memory: None,
+            critical_swap_usage: None,
+            warning_swap_usage: None,
+            critical_swap_in_rate: None,
+            warning_swap_in_rate: None,
+            last_swap_in_sample: Mutex::new(None),
+        }
+    }
+
+    fn fixture_file(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn parses_meminfo_values_as_bytes() {
+        let meminfo = parse_meminfo(MEMINFO_FIXTURE).unwrap();
+        assert_eq!(meminfo.mem_total, 16000000 * 1024);
+        assert_eq!(meminfo.mem_available, 4000000 * 1024);
+        assert_eq!(meminfo.swap_total, 2000000 * 1024);
+        assert_eq!(meminfo.swap_free, 1500000 * 1024);
+    }
+
+    #[test]
+    fn missing_mem_available_is_an_error() {
+        assert!(parse_meminfo("MemTotal: 1000 kB\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn enough_available_memory_returns_success() {
+        let meminfo = fixture_file(MEMINFO_FIXTURE);
+        let check = MemoryCheck {
+            critical_available_memory: Some(Threshold::Percent(10.0)),
+            critical_swap_usage: Some(Threshold::Percent(50.0)),
+            ..make_check(meminfo.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn low_available_memory_returns_failure() {
+        let meminfo = fixture_file(MEMINFO_FIXTURE);
+        let check = MemoryCheck {
+            critical_available_memory: Some(Threshold::Percent(30.0)),
+            ..make_check(meminfo.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        let reason = result.failure_reason.unwrap();
+        assert!(reason.contains("(25.0%) memory available"), "{}", reason);
+    }
+
+    #[tokio::test]
+    async fn high_swap_usage_returns_failure() {
+        let meminfo = fixture_file(MEMINFO_FIXTURE);
+        let check = MemoryCheck {
+            critical_swap_usage: Some(Threshold::Percent(20.0)),
+            ..make_check(meminfo.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        let reason = result.failure_reason.unwrap();
+        assert!(reason.contains("(25.0%) swap used"), "{}", reason);
+    }
+
+    #[tokio::test]
+    async fn low_available_memory_below_warning_returns_warning() {
+        let meminfo = fixture_file(MEMINFO_FIXTURE);
+        let check = MemoryCheck {
+            critical_available_memory: Some(Threshold::Percent(10.0)),
+            warning_available_memory: Some(Threshold::Percent(30.0)),
+            ..make_check(meminfo.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(result.severity, CheckSeverity::Warning);
+        let reason = result.failure_reason.unwrap();
+        assert!(reason.contains("warning at 30%"), "{}", reason);
+    }
+
+    #[test]
+    fn swap_usage_is_ignored_without_swap() {
+        let check = MemoryCheck {
+            critical_swap_usage: Some(Threshold::Absolute(0)),
+            ..make_check(PathBuf::new())
+        };
+        let meminfo = MemInfo {
+            mem_total: 1000,
+            mem_available: 500,
+            swap_total: 0,
+            swap_free: 0,
+        };
+        assert!(check.evaluate(&meminfo, None).is_none());
+    }
+
+    #[test]
+    fn swap_in_rate_is_calculated_between_samples() {
+        let check = make_check(PathBuf::new());
+        let start = Instant::now();
+        let first = SwapInSample {
+            timestamp: start,
+            pages_swapped_in: 100,
+        };
+        assert!(check.update_swap_in_rate(first).is_none());
+
+        let second = SwapInSample {
+            timestamp: start + Duration::from_secs(2),
+            pages_swapped_in: 300,
+        };
+        assert_eq!(check.update_swap_in_rate(second), Some(100.0));
+    }
+
+    #[test]
+    fn high_swap_in_rate_returns_failure() {
+        let check = MemoryCheck {
+            critical_swap_in_rate: Some(50.0),
+            ..make_check(PathBuf::new())
+        };
+        let meminfo = parse_meminfo(MEMINFO_FIXTURE).unwrap();
+        let (_, reason) = check.evaluate(&meminfo, Some(100.0)).unwrap();
+        assert!(reason.contains("swapping in 100.0 pages/s"));
+        assert!(check.evaluate(&meminfo, Some(10.0)).is_none());
+    }
+
+    #[tokio::test]
+    async fn missing_meminfo_file_returns_error() {
+        let check = MemoryCheck {
+            critical_available_memory: Some(Threshold::Percent(10.0)),
+            ..make_check(PathBuf::from("/tmp/easycheck_nonexistent_meminfo"))
+        };
+        assert!(check.execute_check().await.is_err());
+    }
+}
@@ -1,4 +1,13 @@
+pub(crate) mod disk_space_check;
+pub(crate) mod drain_file_check;
+pub(crate) mod exec_check;
+pub(crate) mod file_content_check;
+pub(crate) mod file_freshness_check;
 pub(crate) mod force_success_file_check;
 pub(crate) mod http_response_check;
+pub(crate) mod memory_check;
 pub(crate) mod mtc_file_check;
 pub(crate) mod network_connection_check;
+pub(crate) mod pressure_check;
+pub(crate) mod process_check;
+pub(crate) mod remote_easycheck_check;
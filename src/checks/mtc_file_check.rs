@@ -1,15 +1,115 @@
-use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use anyhow::Context;
 use async_trait::async_trait;
-use tokio::fs;
 
 use crate::options::Options;
-use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+use crate::status::status_checker::{MaintenanceHold, StatusCheckResult, StatusChecker};
+use crate::util::file_watcher::WatchedPath;
+use crate::util::maintenance_info::{inspect_maintenance_file, list_drop_in_files};
+
+/// The path of the mtc file, if none is configured.
+pub(crate) const DEFAULT_MTC_FILE_PATH: &str = "easycheck.disabled";
 
 #[derive(Debug)]
 pub(crate) struct MtcFileCheck {
     file_path: PathBuf,
+    /// The directory in which each file is a separate maintenance hold, if configured.
+    dir_path: Option<PathBuf>,
+    /// The warnings about mtc files that were already logged, keyed by file path.
+    logged_warnings: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl MtcFileCheck {
+    fn new(file_path: PathBuf, dir_path: Option<PathBuf>) -> Self {
+        Self {
+            file_path,
+            dir_path,
+            logged_warnings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Collects the maintenance holds of the mtc file and of all files in the mtc
+    /// directory. The files in the directory are listed by their file name.
+    async fn collect_holds(
+        &self,
+        warnings: &mut HashMap<PathBuf, String>,
+    ) -> anyhow::Result<Vec<MaintenanceHold>> {
+        let mut holds = vec![];
+        if let Some(reason) = self.read_hold(&self.file_path, warnings).await? {
+            holds.push(MaintenanceHold {
+                name: self.check_name(),
+                reason,
+            });
+        }
+        if let Some(dir_path) = &self.dir_path {
+            let file_paths = list_drop_in_files(dir_path)
+                .await
+                .with_context(|| format!("unable to list {:?}", dir_path))?;
+            for file_path in file_paths {
+                if let Some(reason) = self.read_hold(&file_path, warnings).await? {
+                    let file_name = file_path.file_name().unwrap_or_default();
+                    holds.push(MaintenanceHold {
+                        name: format!("{} {}", self.check_name(), file_name.to_string_lossy()),
+                        reason,
+                    });
+                }
+            }
+        }
+        Ok(holds)
+    }
+
+    /// Reads the given mtc file. Returns the reason of the maintenance hold, or None if
+    /// the file does not exist or its maintenance ended. An mtc file whose maintenance
+    /// ended is ignored, so that a forgotten file does not keep the server unavailable.
+    async fn read_hold(
+        &self,
+        file_path: &Path,
+        warnings: &mut HashMap<PathBuf, String>,
+    ) -> anyhow::Result<Option<String>> {
+        let Some(file) = inspect_maintenance_file(file_path)
+            .await
+            .with_context(|| format!("unable to read {:?}", file_path))?
+        else {
+            // file does not exist, no maintenance hold
+            return Ok(None);
+        };
+
+        let ended = file.info.has_ended();
+        let warning = match (file.problem, &file.info.until) {
+            (Some(problem), _) => Some(problem),
+            (None, Some(until)) if ended => Some(format!("maintenance ended at {}", until)),
+            _ => None,
+        };
+        if let Some(warning) = warning {
+            self.warn_once(file_path, warning, warnings);
+        }
+        if ended {
+            return Ok(None);
+        }
+
+        match file.info.describe() {
+            Some(description) => Ok(Some(format!("mtc file exists: {}", description))),
+            None => Ok(Some(String::from("mtc file exists"))),
+        }
+    }
+
+    /// Logs the given warning about an mtc file, unless it was already logged during the
+    /// previous execution, so that the log is not flooded on every check execution.
+    fn warn_once(
+        &self,
+        file_path: &Path,
+        warning: String,
+        warnings: &mut HashMap<PathBuf, String>,
+    ) {
+        let logged_warnings = self.logged_warnings.lock().unwrap();
+        if logged_warnings.get(file_path) != Some(&warning) {
+            log::warn!("mtc file {:?}: {}", file_path, warning);
+        }
+        warnings.insert(file_path.to_path_buf(), warning);
+    }
 }
 
 #[async_trait]
@@ -18,33 +118,51 @@ impl StatusChecker for MtcFileCheck {
         let mtc_file_path = options
             .mtc_check_file_path
             .to_owned()
-            .unwrap_or_else(|| String::from("easycheck.disabled"));
+            .unwrap_or_else(|| String::from(DEFAULT_MTC_FILE_PATH));
         let file_path = PathBuf::from(mtc_file_path);
-        Ok(Some(Self { file_path }))
+        let dir_path = options.mtc_check_dir_path.as_ref().map(PathBuf::from);
+        Ok(Some(Self::new(file_path, dir_path)))
     }
 
     fn check_name(&self) -> String {
         String::from("mtc file")
     }
 
+    fn check_id(&self) -> &str {
+        "mtc"
+    }
+
+    fn watched_paths(&self) -> Vec<WatchedPath> {
+        let mut watched_paths = vec![WatchedPath::File(self.file_path.clone())];
+        watched_paths.extend(self.dir_path.clone().map(WatchedPath::Directory));
+        watched_paths
+    }
+
     async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
-        log::debug!("checking mtc file at {:?}", &self.file_path);
-        match fs::metadata(&self.file_path).await {
-            Ok(_) => {
-                let reason = String::from("mtc file exists");
-                let check_result = StatusCheckResult::new_failure(reason);
+        log::debug!(
+            "checking mtc file at {:?} and directory {:?}",
+            &self.file_path,
+            &self.dir_path
+        );
+        let mut warnings = HashMap::new();
+        let holds = self.collect_holds(&mut warnings).await;
+        *self.logged_warnings.lock().unwrap() = warnings;
+        match holds {
+            // no mtc file exists, check is successful
+            Ok(holds) if holds.is_empty() => Ok(StatusCheckResult::new_success()),
+            Ok(holds) => {
+                let reasons: Vec<&str> = holds.iter().map(|hold| hold.reason.as_str()).collect();
+                let reason = reasons.join("; ");
+                let check_result = StatusCheckResult::new_failure(reason)
+                    .maintenance_hold()
+                    .with_holds(holds);
                 Ok(check_result)
             }
             Err(error) => {
-                if error.kind() == ErrorKind::NotFound {
-                    // file does not exist, check is successful
-                    Ok(StatusCheckResult::new_success())
-                } else {
-                    // unable to query mtc file metadata
-                    let reason = format!("unable to query mtc existence: {}", error);
-                    let check_result = StatusCheckResult::new_failure(reason);
-                    Ok(check_result)
-                }
+                // unable to read the mtc files
+                let reason = format!("unable to query mtc existence: {:#}", error);
+                let check_result = StatusCheckResult::new_failure(reason);
+                Ok(check_result)
             }
         }
     }
@@ -53,23 +171,125 @@ impl StatusChecker for MtcFileCheck {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
     use tempfile::NamedTempFile;
 
     #[tokio::test]
     async fn file_present_returns_failure() {
         let tmp = NamedTempFile::new().unwrap();
-        let check = MtcFileCheck {
-            file_path: tmp.path().to_path_buf(),
-        };
+        let check = MtcFileCheck::new(tmp.path().to_path_buf(), None);
         let result = check.execute_check().await.unwrap();
         assert_eq!(result.failure_reason.as_deref(), Some("mtc file exists"));
+        assert!(result.maintenance);
     }
 
     #[tokio::test]
     async fn file_absent_returns_success() {
-        let check = MtcFileCheck {
-            file_path: PathBuf::from("/tmp/easycheck_nonexistent_mtc_file_test"),
-        };
+        let check = MtcFileCheck::new(
+            PathBuf::from("/tmp/easycheck_nonexistent_mtc_file_test"),
+            None,
+        );
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn file_with_details_returns_failure_with_reason() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        writeln!(
+            tmp,
+            "reason=kernel update\nby=jane\nuntil=2999-01-01T00:00:00Z"
+        )
+        .unwrap();
+        let check = MtcFileCheck::new(tmp.path().to_path_buf(), None);
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("mtc file exists: kernel update (by jane, until 2999-01-01T00:00:00Z)")
+        );
+        assert!(result.maintenance);
+    }
+
+    #[tokio::test]
+    async fn file_with_expired_until_returns_success() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        write!(
+            tmp,
+            r#"{{"reason": "deployment", "until": "2020-01-01T00:00:00Z"}}"#
+        )
+        .unwrap();
+        let check = MtcFileCheck::new(tmp.path().to_path_buf(), None);
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+        assert!(!result.maintenance);
+    }
+
+    #[tokio::test]
+    async fn file_with_invalid_contents_returns_failure() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        write!(tmp, "{{not json").unwrap();
+        let check = MtcFileCheck::new(tmp.path().to_path_buf(), None);
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(result.failure_reason.as_deref(), Some("mtc file exists"));
+        assert!(result.maintenance);
+    }
+
+    #[tokio::test]
+    async fn warnings_are_tracked_while_file_is_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.disabled");
+        std::fs::write(&file_path, "{not json").unwrap();
+        let check = MtcFileCheck::new(file_path.clone(), None);
+        for _ in 0..2 {
+            check.execute_check().await.unwrap();
+            let logged_warnings = check.logged_warnings.lock().unwrap();
+            assert!(logged_warnings[&file_path].starts_with("unable to parse details"));
+        }
+
+        std::fs::write(&file_path, "until=2020-01-01T00:00:00Z").unwrap();
+        check.execute_check().await.unwrap();
+        assert_eq!(
+            check.logged_warnings.lock().unwrap()[&file_path],
+            "maintenance ended at 2020-01-01T00:00:00Z"
+        );
+
+        std::fs::remove_file(&file_path).unwrap();
+        check.execute_check().await.unwrap();
+        assert!(check.logged_warnings.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn files_in_dir_are_separate_holds() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("dba"), "reason=index rebuild").unwrap();
+        std::fs::write(dir.path().join("deploy"), "").unwrap();
+        std::fs::write(dir.path().join("expired"), "until=2020-01-01T00:00:00Z").unwrap();
+        let check = MtcFileCheck::new(
+            dir.path().join("easycheck.disabled"),
+            Some(dir.path().to_path_buf()),
+        );
+        let result = check.execute_check().await.unwrap();
+        assert!(result.maintenance);
+        assert_eq!(
+            result.holds,
+            vec![
+                MaintenanceHold {
+                    name: "mtc file dba".to_string(),
+                    reason: "mtc file exists: index rebuild".to_string(),
+                },
+                MaintenanceHold {
+                    name: "mtc file deploy".to_string(),
+                    reason: "mtc file exists".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("mtc file exists: index rebuild; mtc file exists")
+        );
+
+        std::fs::remove_file(dir.path().join("dba")).unwrap();
+        std::fs::remove_file(dir.path().join("deploy")).unwrap();
         let result = check.execute_check().await.unwrap();
         assert!(result.failure_reason.is_none());
     }
@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::Path;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -7,11 +8,15 @@ use tokio::time::timeout;
 
 use crate::options::Options;
 use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+use crate::util::proxy_protocol::{encode_proxy_header, ProxyProtocolSettings};
 use crate::util::tcp_connector::{RealTcpConnector, TcpConnector};
+use crate::util::tls_connector::TlsConnector;
 
 pub(crate) struct NetworkConnectionCheck {
     target_address: SocketAddr,
     read_initial_response: bool,
+    proxy_protocol_header: Option<Vec<u8>>,
+    tls_connector: Option<TlsConnector>,
     connector: Box<dyn TcpConnector>,
 }
 
@@ -23,9 +28,32 @@ impl StatusChecker for NetworkConnectionCheck {
             Some(target_address) => {
                 let read_initial_response =
                     options.socket_check_read_initial_response.unwrap_or(false);
+                let proxy_protocol_header = match &options.socket_proxy_protocol_version {
+                    Some(version) => {
+                        let settings = ProxyProtocolSettings::from_options(options)?;
+                        Some(encode_proxy_header(version, &settings)?)
+                    }
+                    None => None,
+                };
+                let tls_connector = if options.socket_check_tls.unwrap_or(false) {
+                    let tls_connector = TlsConnector::new(
+                        &target_address,
+                        options.socket_check_tls_server_name.as_deref(),
+                        options
+                            .socket_check_tls_ca_file_path
+                            .as_deref()
+                            .map(Path::new),
+                    )?;
+                    Some(tls_connector)
+                } else {
+                    None
+                };
+
                 Ok(Some(Self {
                     target_address,
                     read_initial_response,
+                    proxy_protocol_header,
+                    tls_connector,
                     connector: Box::new(RealTcpConnector),
                 }))
             }
@@ -36,11 +64,17 @@ impl StatusChecker for NetworkConnectionCheck {
         format!("network connection check {}", self.target_address)
     }
 
+    fn check_id(&self) -> &str {
+        "socket"
+    }
+
     async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
         log::debug!(
-            "checking network connection to {} (read_initial_response={})",
+            "checking network connection to {} (read_initial_response={}, proxy_protocol={}, tls={})",
             self.target_address,
-            self.read_initial_response
+            self.read_initial_response,
+            self.proxy_protocol_header.is_some(),
+            self.tls_connector.is_some()
         );
         match timeout(Duration::from_secs(5), async {
             let mut stream = match self.connector.connect(&self.target_address).await {
@@ -52,6 +86,27 @@ impl StatusChecker for NetworkConnectionCheck {
                 Ok(stream) => stream,
             };
 
+            if let Some(proxy_protocol_header) = &self.proxy_protocol_header {
+                if let Err(err) = stream.write_all(proxy_protocol_header).await {
+                    let failure_reason = format!(
+                        "error sending proxy protocol header to {}: {}",
+                        self.target_address, err
+                    );
+                    return Ok(StatusCheckResult::new_failure(failure_reason));
+                }
+            }
+
+            if let Some(tls_connector) = &self.tls_connector {
+                stream = match tls_connector.wrap(stream).await {
+                    Err(err) => {
+                        let failure_reason =
+                            format!("tls handshake with {} failed: {}", self.target_address, err);
+                        return Ok(StatusCheckResult::new_failure(failure_reason));
+                    }
+                    Ok(tls_stream) => tls_stream,
+                };
+            }
+
             if self.read_initial_response {
                 if let Some(result) = Self::read_and_discard_response(&mut stream).await {
                     return Ok(result);
@@ -101,7 +156,9 @@ impl NetworkConnectionCheck {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::ProxyProtocolVersion;
     use crate::util::tcp_connector::AsyncStream;
+    use crate::util::tls_connector::TEST_CA_FILE_PATH;
     use std::io;
     use std::net::SocketAddr;
     use std::pin::Pin;
@@ -154,6 +211,8 @@ mod tests {
         let check = NetworkConnectionCheck {
             target_address: dummy_addr(),
             read_initial_response: false,
+            proxy_protocol_header: None,
+            tls_connector: None,
             connector: Box::new(MockConnector::new(mock_stream)),
         };
         let result = check.execute_check().await.unwrap();
@@ -165,6 +224,8 @@ mod tests {
         let check = NetworkConnectionCheck {
             target_address: dummy_addr(),
             read_initial_response: false,
+            proxy_protocol_header: None,
+            tls_connector: None,
             connector: Box::new(FailingConnector {
                 error_kind: io::ErrorKind::ConnectionRefused,
             }),
@@ -188,18 +249,69 @@ mod tests {
         let check = NetworkConnectionCheck {
             target_address: dummy_addr(),
             read_initial_response: true,
+            proxy_protocol_header: None,
+            tls_connector: None,
+            connector: Box::new(MockConnector::new(mock_stream)),
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn proxy_protocol_header_is_sent_before_quit() {
+        let expected_header =
+            encode_proxy_header(&ProxyProtocolVersion::V2, &ProxyProtocolSettings::default())
+                .unwrap();
+        let mock_stream = tokio_test::io::Builder::new()
+            .write(&expected_header)
+            .write(b"QUIT\n")
+            .read(b"goodbye")
+            .build();
+
+        let check = NetworkConnectionCheck {
+            target_address: dummy_addr(),
+            read_initial_response: false,
+            proxy_protocol_header: Some(expected_header.clone()),
+            tls_connector: None,
             connector: Box::new(MockConnector::new(mock_stream)),
         };
         let result = check.execute_check().await.unwrap();
         assert!(result.failure_reason.is_none());
     }
 
+    #[tokio::test]
+    async fn tls_handshake_failure_returns_failure() {
+        let ca_file_path = Path::new(TEST_CA_FILE_PATH);
+        let tls_connector = TlsConnector::new(&dummy_addr(), None, Some(ca_file_path)).unwrap();
+        let (client_stream, mut server_stream) = tokio::io::duplex(8192);
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 1024];
+            let _ = server_stream.read(&mut buffer).await;
+            let _ = server_stream.write_all(b"220 not tls\r\n").await;
+        });
+
+        let check = NetworkConnectionCheck {
+            target_address: dummy_addr(),
+            read_initial_response: false,
+            proxy_protocol_header: None,
+            tls_connector: Some(tls_connector),
+            connector: Box::new(MockConnector::new(client_stream)),
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result
+            .failure_reason
+            .unwrap()
+            .contains("tls handshake with"));
+    }
+
     #[test]
     fn check_name_contains_address() {
         let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
         let check = NetworkConnectionCheck {
             target_address: addr,
             read_initial_response: false,
+            proxy_protocol_header: None,
+            tls_connector: None,
             connector: Box::new(RealTcpConnector),
         };
         let name = check.check_name();
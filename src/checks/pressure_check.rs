@@ -0,0 +1,441 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use clap::ValueEnum;
+use tokio::fs;
+
+use crate::options::Options;
+use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+
+/// The resources for which the kernel reports pressure stall information.
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PressureResource {
+    Cpu,
+    Memory,
+    Io,
+}
+
+/// The pressure line, either some tasks or all non-idle tasks are stalled.
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PressureKind {
+    Some,
+    Full,
+}
+
+/// The averaging window of the pressure value.
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PressureWindow {
+    Avg10,
+    Avg60,
+}
+
+/// A maximum for a single pressure stall value, in the format
+/// `<resource>.<kind>.<window>=<max percent>`, e.g. `memory.full.avg10=5`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureThreshold {
+    resource: PressureResource,
+    kind: PressureKind,
+    window: PressureWindow,
+    max_percent: f64,
+}
+
+impl FromStr for PressureThreshold {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (selector, max_percent) = value
+            .split_once('=')
+            .ok_or_else(|| format!("missing '=' in pressure threshold {}", value))?;
+        let selector_parts: Vec<_> = selector.trim().split('.').collect();
+        let [resource, kind, window] = selector_parts[..] else {
+            return Err(format!(
+                "pressure threshold selector {} must have the format <resource>.<kind>.<window>",
+                selector
+            ));
+        };
+
+        Ok(Self {
+            resource: PressureResource::from_str(resource, true)?,
+            kind: PressureKind::from_str(kind, true)?,
+            window: PressureWindow::from_str(window, true)?,
+            max_percent: max_percent
+                .trim()
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .map_err(|err| format!("invalid pressure threshold {}: {}", value, err))?,
+        })
+    }
+}
+
+impl Display for PressureThreshold {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.resource.to_possible_value().unwrap().get_name(),
+            self.kind.to_possible_value().unwrap().get_name(),
+            self.window.to_possible_value().unwrap().get_name()
+        )
+    }
+}
+
+/// The averages of a single line of a pressure file.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PressureAverages {
+    avg10: f64,
+    avg60: f64,
+}
+
+/// The parsed content of a single pressure file.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PressureValues {
+    some: PressureAverages,
+    full: PressureAverages,
+}
+
+impl PressureValues {
+    fn value(&self, kind: PressureKind, window: PressureWindow) -> f64 {
+        let averages = match kind {
+            PressureKind::Some => &self.some,
+            PressureKind::Full => &self.full,
+        };
+        match window {
+            PressureWindow::Avg10 => averages.avg10,
+            PressureWindow::Avg60 => averages.avg60,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PressureCheck {
+    pressure_directory: PathBuf,
+    loadavg_path: PathBuf,
+    cpu_count: usize,
+    critical_pressure: Vec<PressureThreshold>,
+    warning_pressure: Vec<PressureThreshold>,
+    critical_load_per_cpu: Option<f64>,
+    warning_load_per_cpu: Option<f64>,
+}
+
+#[async_trait]
+impl StatusChecker for PressureCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        let check = Self {
+            pressure_directory: options
+                .pressure_directory
+                .to_owned()
+                .unwrap_or_else(|| PathBuf::from("/proc/pressure")),
+            loadavg_path: options
+                .loadavg_path
+                .to_owned()
+                .unwrap_or_else(|| PathBuf::from("/proc/loadavg")),
+            cpu_count: std::thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(1),
+            critical_pressure: options.pressure_max.to_owned().unwrap_or_default(),
+            warning_pressure: options.pressure_warn.to_owned().unwrap_or_default(),
+            critical_load_per_cpu: options.load_max_per_cpu,
+            warning_load_per_cpu: options.load_warn_per_cpu,
+        };
+
+        // the check is only enabled if at least one threshold is configured
+        let enabled = !check.critical_pressure.is_empty()
+            || !check.warning_pressure.is_empty()
+            || check.critical_load_per_cpu.is_some()
+            || check.warning_load_per_cpu.is_some();
+        Ok(enabled.then_some(check))
+    }
+
+    fn check_name(&self) -> String {
+        String::from("system pressure check")
+    }
+
+    fn check_id(&self) -> &str {
+        "pressure"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        let mut critical_reasons = vec![];
+        let mut warnings = vec![];
+
+        for resource in [
+            PressureResource::Cpu,
+            PressureResource::Memory,
+            PressureResource::Io,
+        ] {
+            let critical: Vec<_> = self
+                .critical_pressure
+                .iter()
+                .filter(|threshold| threshold.resource == resource)
+                .collect();
+            let warning: Vec<_> = self
+                .warning_pressure
+                .iter()
+                .filter(|threshold| threshold.resource == resource)
+                .collect();
+            if critical.is_empty() && warning.is_empty() {
+                continue;
+            }
+
+            let values = self.read_pressure_values(resource).await?;
+            critical_reasons.extend(Self::evaluate_pressure(&values, &critical));
+            warnings.extend(Self::evaluate_pressure(&values, &warning));
+        }
+
+        if self.critical_load_per_cpu.is_some() || self.warning_load_per_cpu.is_some() {
+            log::debug!("checking load average from {:?}", &self.loadavg_path);
+            let loadavg_content = fs::read_to_string(&self.loadavg_path)
+                .await
+                .with_context(|| format!("unable to read {}", self.loadavg_path.display()))?;
+            let load = parse_load_average(&loadavg_content)?;
+            let load_per_cpu = load / self.cpu_count as f64;
+            let load_description = format!(
+                "load {:.2} on {} cpus ({:.2} per cpu)",
+                load, self.cpu_count, load_per_cpu
+            );
+            if let Some(max) = self.critical_load_per_cpu.filter(|max| load_per_cpu > *max) {
+                critical_reasons.push(format!("{}, maximum is {}", load_description, max));
+            } else if let Some(max) = self.warning_load_per_cpu.filter(|max| load_per_cpu > *max) {
+                warnings.push(format!("{}, warning at {}", load_description, max));
+            }
+        }
+
+        if !critical_reasons.is_empty() {
+            return Ok(StatusCheckResult::new_failure(critical_reasons.join(", ")));
+        }
+        if !warnings.is_empty() {
+            return Ok(StatusCheckResult::new_warning(warnings.join(", ")));
+        }
+        Ok(StatusCheckResult::new_success())
+    }
+}
+
+impl PressureCheck {
+    async fn read_pressure_values(
+        &self,
+        resource: PressureResource,
+    ) -> anyhow::Result<PressureValues> {
+        let file_name = resource.to_possible_value().unwrap().get_name().to_string();
+        let pressure_path = self.pressure_directory.join(file_name);
+        log::debug!(
+            "checking pressure stall information from {:?}",
+            pressure_path
+        );
+        let content = fs::read_to_string(&pressure_path)
+            .await
+            .with_context(|| format!("unable to read {}", pressure_path.display()))?;
+        parse_pressure_values(&content)
+    }
+
+    /// Returns a description for each of the given thresholds that is exceeded.
+    fn evaluate_pressure(
+        values: &PressureValues,
+        thresholds: &[&PressureThreshold],
+    ) -> Vec<String> {
+        thresholds
+            .iter()
+            .filter_map(|threshold| {
+                let value = values.value(threshold.kind, threshold.window);
+                (value > threshold.max_percent).then(|| {
+                    format!(
+                        "{} pressure at {:.2}%, maximum is {}%",
+                        threshold, value, threshold.max_percent
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parses the content of a pressure file, in the format:
+/// `some avg10=0.00 avg60=0.00 avg300=0.00 total=0` (and the same for `full`).
+/// Missing lines (for example `full` for cpu on older kernels) default to zero.
+fn parse_pressure_values(content: &str) -> anyhow::Result<PressureValues> {
+    let mut values = PressureValues::default();
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let averages = match parts.next() {
+            Some("some") => &mut values.some,
+            Some("full") => &mut values.full,
+            _ => continue,
+        };
+        for part in parts {
+            let Some((key, value)) = part.split_once('=') else {
+                continue;
+            };
+            let target = match key {
+                "avg10" => &mut averages.avg10,
+                "avg60" => &mut averages.avg60,
+                _ => continue,
+            };
+            *target = value
+                .parse::<f64>()
+                .with_context(|| format!("invalid pressure value {}", part))?;
+        }
+    }
+    Ok(values)
+}
+
+/// Parses the one minute load average from the content of /proc/loadavg.
+fn parse_load_average(content: &str) -> anyhow::Result<f64> {
+    let load = content
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("loadavg file is empty"))?;
+    load.parse::<f64>()
+        .with_context(|| format!("invalid load average {}", load))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::status_checker::CheckSeverity;
+    use std::fs as std_fs;
+    use tempfile::TempDir;
+
+    const MEMORY_PRESSURE_FIXTURE: &str = "some avg10=12.50 avg60=4.00 avg300=1.00 total=12345
+full avg10=6.25 avg60=2.00 avg300=0.50 total=6789
+";
+
+    fn make_check(pressure_directory: PathBuf, loadavg_path: PathBuf) -> PressureCheck {
+        PressureCheck {
+            pressure_directory,
+            loadavg_path,
+            cpu_count: 4,
+            critical_pressure: vec![],
+            warning_pressure: vec![],
+            critical_load_per_cpu: None,
+            warning_load_per_cpu: None,
+        }
+    }
+
+    fn fixture_directory() -> TempDir {
+        let directory = tempfile::tempdir().unwrap();
+        std_fs::write(directory.path().join("memory"), MEMORY_PRESSURE_FIXTURE).unwrap();
+        std_fs::write(
+            directory.path().join("cpu"),
+            "some avg10=1.00 avg60=1.00 avg300=1.00 total=1\n",
+        )
+        .unwrap();
+        std_fs::write(
+            directory.path().join("loadavg"),
+            "6.00 4.00 2.00 3/512 4242\n",
+        )
+        .unwrap();
+        directory
+    }
+
+    #[test]
+    fn parses_pressure_threshold() {
+        let threshold: PressureThreshold = "memory.full.avg10=5".parse().unwrap();
+        assert_eq!(threshold.resource, PressureResource::Memory);
+        assert_eq!(threshold.kind, PressureKind::Full);
+        assert_eq!(threshold.window, PressureWindow::Avg10);
+        assert_eq!(threshold.max_percent, 5.0);
+        assert_eq!(threshold.to_string(), "memory.full.avg10");
+
+        assert!("memory.full=5".parse::<PressureThreshold>().is_err());
+        assert!("disk.some.avg10=5".parse::<PressureThreshold>().is_err());
+        assert!("cpu.some.avg10".parse::<PressureThreshold>().is_err());
+    }
+
+    #[test]
+    fn parses_pressure_file() {
+        let values = parse_pressure_values(MEMORY_PRESSURE_FIXTURE).unwrap();
+        assert_eq!(
+            values.value(PressureKind::Some, PressureWindow::Avg10),
+            12.5
+        );
+        assert_eq!(values.value(PressureKind::Some, PressureWindow::Avg60), 4.0);
+        assert_eq!(
+            values.value(PressureKind::Full, PressureWindow::Avg10),
+            6.25
+        );
+        assert_eq!(values.value(PressureKind::Full, PressureWindow::Avg60), 2.0);
+    }
+
+    #[test]
+    fn parses_load_average() {
+        assert_eq!(
+            parse_load_average("0.52 0.58 0.59 1/467 1234\n").unwrap(),
+            0.52
+        );
+        assert!(parse_load_average("").is_err());
+    }
+
+    #[tokio::test]
+    async fn pressure_below_threshold_returns_success() {
+        let directory = fixture_directory();
+        let check = PressureCheck {
+            critical_pressure: vec!["memory.some.avg10=20".parse().unwrap()],
+            ..make_check(directory.path().to_path_buf(), PathBuf::new())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn pressure_above_threshold_returns_failure() {
+        let directory = fixture_directory();
+        let check = PressureCheck {
+            critical_pressure: vec![
+                "memory.full.avg10=5".parse().unwrap(),
+                "cpu.some.avg60=5".parse().unwrap(),
+            ],
+            ..make_check(directory.path().to_path_buf(), PathBuf::new())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("memory.full.avg10 pressure at 6.25%, maximum is 5%")
+        );
+    }
+
+    #[tokio::test]
+    async fn warning_pressure_returns_warning() {
+        let directory = fixture_directory();
+        let check = PressureCheck {
+            warning_pressure: vec!["memory.some.avg10=1".parse().unwrap()],
+            ..make_check(directory.path().to_path_buf(), PathBuf::new())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(result.severity, CheckSeverity::Warning);
+        assert!(result.failure_reason.is_some());
+    }
+
+    #[tokio::test]
+    async fn high_load_per_cpu_returns_failure() {
+        let directory = fixture_directory();
+        let check = PressureCheck {
+            critical_load_per_cpu: Some(1.0),
+            ..make_check(PathBuf::new(), directory.path().join("loadavg"))
+        };
+        let result = check.execute_check().await.unwrap();
+        let reason = result.failure_reason.unwrap();
+        assert!(
+            reason.contains("load 6.00 on 4 cpus (1.50 per cpu)"),
+            "{}",
+            reason
+        );
+
+        let check = PressureCheck {
+            critical_load_per_cpu: Some(2.0),
+            ..make_check(PathBuf::new(), directory.path().join("loadavg"))
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn missing_pressure_file_returns_error() {
+        let directory = fixture_directory();
+        let check = PressureCheck {
+            critical_pressure: vec!["io.some.avg10=5".parse().unwrap()],
+            ..make_check(directory.path().to_path_buf(), PathBuf::new())
+        };
+        assert!(check.execute_check().await.is_err());
+    }
+}
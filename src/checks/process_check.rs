@@ -0,0 +1,464 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use regex::Regex;
+use tokio::fs;
+
+use crate::options::Options;
+use crate::status::status_checker::{StatusCheckResult, StatusChecker};
+
+/// A process that matched the configured selectors.
+#[derive(Debug, Clone, PartialEq)]
+struct ProcessInfo {
+    pid: u32,
+    name: String,
+    state: char,
+}
+
+#[derive(Debug)]
+pub(crate) struct ProcessCheck {
+    proc_path: PathBuf,
+    pidfile_path: Option<PathBuf>,
+    process_name: Option<String>,
+    cmdline_pattern: Option<Regex>,
+    min_count: usize,
+    max_count: Option<usize>,
+    fail_on_zombie: bool,
+    fail_on_uninterruptible: bool,
+}
+
+#[async_trait]
+impl StatusChecker for ProcessCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        if options.process_pidfile_path.is_none()
+            && options.process_name.is_none()
+            && options.process_cmdline_pattern.is_none()
+        {
+            return Ok(None);
+        }
+
+        let cmdline_pattern = match &options.process_cmdline_pattern {
+            Some(pattern) => Some(
+                Regex::new(pattern)
+                    .with_context(|| format!("invalid process cmdline pattern {}", pattern))?,
+            ),
+            None => None,
+        };
+        let min_count = options.process_min_count.unwrap_or(1);
+        if let Some(max_count) = options.process_max_count {
+            if max_count < min_count {
+                bail!(
+                    "process max count {} must not be lower than min count {}",
+                    max_count,
+                    min_count
+                );
+            }
+        }
+
+        Ok(Some(Self {
+            proc_path: options
+                .proc_path
+                .to_owned()
+                .unwrap_or_else(|| PathBuf::from("/proc")),
+            pidfile_path: options.process_pidfile_path.to_owned(),
+            process_name: options.process_name.to_owned(),
+            cmdline_pattern,
+            min_count,
+            max_count: options.process_max_count,
+            fail_on_zombie: options.process_fail_on_zombie.unwrap_or(false),
+            fail_on_uninterruptible: options.process_fail_on_uninterruptible.unwrap_or(false),
+        }))
+    }
+
+    fn check_name(&self) -> String {
+        let mut selectors = vec![];
+        if let Some(pidfile_path) = &self.pidfile_path {
+            selectors.push(format!("pidfile {}", pidfile_path.display()));
+        }
+        if let Some(process_name) = &self.process_name {
+            selectors.push(format!("name {}", process_name));
+        }
+        if let Some(cmdline_pattern) = &self.cmdline_pattern {
+            selectors.push(format!("cmdline {}", cmdline_pattern));
+        }
+        format!("process check {}", selectors.join(", "))
+    }
+
+    fn check_id(&self) -> &str {
+        "process"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        let candidate_pids = match &self.pidfile_path {
+            Some(pidfile_path) => match self.read_pidfile(pidfile_path).await {
+                Ok(pid) => vec![pid],
+                Err(error) => return Ok(StatusCheckResult::new_failure(format!("{:#}", error))),
+            },
+            None => self.list_pids().await?,
+        };
+
+        let mut processes = vec![];
+        for pid in candidate_pids {
+            if let Some(process) = self.read_matching_process(pid).await? {
+                processes.push(process);
+            }
+        }
+        log::debug!("found {} matching processes", processes.len());
+
+        Ok(self.evaluate_processes(&processes))
+    }
+}
+
+impl ProcessCheck {
+    /// Reads the pid from the given pidfile.
+    async fn read_pidfile(&self, pidfile_path: &Path) -> anyhow::Result<u32> {
+        let content = fs::read_to_string(pidfile_path)
+            .await
+            .with_context(|| format!("unable to read pidfile {}", pidfile_path.display()))?;
+        content
+            .trim()
+            .parse::<u32>()
+            .with_context(|| format!("invalid pid in pidfile {}", pidfile_path.display()))
+    }
+
+    /// Lists the pids of all running processes.
+    async fn list_pids(&self) -> anyhow::Result<Vec<u32>> {
+        let mut pids = vec![];
+        let mut entries = fs::read_dir(&self.proc_path)
+            .await
+            .with_context(|| format!("unable to list {}", self.proc_path.display()))?;
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse().ok())
+            {
+                pids.push(pid);
+            }
+        }
+        Ok(pids)
+    }
+
+    /// Reads the information of the process with the given pid. Returns None if
+    /// the process does not exist (anymore) or does not match the selectors.
+    async fn read_matching_process(&self, pid: u32) -> anyhow::Result<Option<ProcessInfo>> {
+        let process_path = self.proc_path.join(pid.to_string());
+        let Some(stat) = read_process_file(&process_path.join("stat")).await else {
+            return Ok(None);
+        };
+        let Some((name, state)) = parse_stat(&stat) else {
+            bail!("unable to parse stat of process {}", pid);
+        };
+
+        if self
+            .process_name
+            .as_deref()
+            .is_some_and(|process_name| process_name != name)
+        {
+            return Ok(None);
+        }
+        if let Some(cmdline_pattern) = &self.cmdline_pattern {
+            let Some(cmdline) = read_process_file(&process_path.join("cmdline")).await else {
+                return Ok(None);
+            };
+            let cmdline = cmdline.trim_end_matches('\0').replace('\0', " ");
+            // zombies and kernel threads have no cmdline, their name is matched instead
+            let cmdline = if cmdline.is_empty() { name } else { &cmdline };
+            if !cmdline_pattern.is_match(cmdline) {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(ProcessInfo {
+            pid,
+            name: name.to_string(),
+            state,
+        }))
+    }
+
+    /// Evaluates the matching processes against the configured instance counts and states.
+    fn evaluate_processes(&self, processes: &[ProcessInfo]) -> StatusCheckResult {
+        if let Some(pidfile_path) = &self.pidfile_path {
+            if processes.is_empty() {
+                return StatusCheckResult::new_failure(format!(
+                    "process from pidfile {} is not running",
+                    pidfile_path.display()
+                ));
+            }
+        }
+
+        let count = processes.len();
+        if count < self.min_count {
+            return StatusCheckResult::new_failure(format!(
+                "{} matching processes running, minimum is {}",
+                count, self.min_count
+            ));
+        }
+        if let Some(max_count) = self.max_count.filter(|max_count| count > *max_count) {
+            return StatusCheckResult::new_failure(format!(
+                "{} matching processes running, maximum is {}",
+                count, max_count
+            ));
+        }
+
+        let failing_states: Vec<_> = processes
+            .iter()
+            .filter_map(|process| match process.state {
+                'Z' if self.fail_on_zombie => Some(format!(
+                    "process {} ({}) is a zombie",
+                    process.pid, process.name
+                )),
+                'D' if self.fail_on_uninterruptible => Some(format!(
+                    "process {} ({}) is in uninterruptible sleep",
+                    process.pid, process.name
+                )),
+                _ => None,
+            })
+            .collect();
+        if !failing_states.is_empty() {
+            return StatusCheckResult::new_failure(failing_states.join(", "));
+        }
+
+        StatusCheckResult::new_success()
+    }
+}
+
+/// Reads the given file of a process to a string. Returns None if the file cannot be read,
+/// as the process may exit at any time, which makes reads fail with different errors.
+async fn read_process_file(path: &Path) -> Option<String> {
+    match fs::read(path).await {
+        Ok(content) => Some(String::from_utf8_lossy(&content).into_owned()),
+        Err(error) => {
+            log::debug!(
+                "skipping process, unable to read {}: {}",
+                path.display(),
+                error
+            );
+            None
+        }
+    }
+}
+
+/// Parses the process name and state from the content of /proc/<pid>/stat, in the
+/// format `<pid> (<name>) <state> ...`. The name itself may contain spaces and
+/// parentheses, therefore everything up to the last closing parenthesis is used.
+fn parse_stat(stat: &str) -> Option<(&str, char)> {
+    let name_start = stat.find('(')?;
+    let name_end = stat.rfind(')')?;
+    let name = stat.get(name_start + 1..name_end)?;
+    let state = stat.get(name_end + 1..)?.trim_start().chars().next()?;
+    Some((name, state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs as std_fs;
+    use tempfile::TempDir;
+
+    fn make_check(proc_path: PathBuf) -> ProcessCheck {
+        ProcessCheck {
+            proc_path,
+            pidfile_path: None,
+            process_name: None,
+            cmdline_pattern: None,
+            min_count: 1,
+            max_count: None,
+            fail_on_zombie: false,
+            fail_on_uninterruptible: false,
+        }
+    }
+
+    fn add_process(proc_dir: &TempDir, pid: u32, name: &str, state: char, cmdline: &[&str]) {
+        let process_dir = proc_dir.path().join(pid.to_string());
+        std_fs::create_dir(&process_dir).unwrap();
+        let stat = format!("{} ({}) {} 1 {} {} 0", pid, name, state, pid, pid);
+        std_fs::write(process_dir.join("stat"), stat).unwrap();
+        let mut cmdline = cmdline.join("\0");
+        cmdline.push('\0');
+        std_fs::write(process_dir.join("cmdline"), cmdline).unwrap();
+    }
+
+    fn fixture_proc() -> TempDir {
+        let proc_dir = tempfile::tempdir().unwrap();
+        add_process(&proc_dir, 1, "systemd", 'S', &["/sbin/init"]);
+        add_process(
+            &proc_dir,
+            100,
+            "php",
+            'S',
+            &["php", "artisan", "queue:work", "redis"],
+        );
+        add_process(
+            &proc_dir,
+            101,
+            "php",
+            'R',
+            &["php", "artisan", "queue:work", "sqs"],
+        );
+        add_process(&proc_dir, 102, "php-fpm", 'D', &["php-fpm: master process"]);
+        std_fs::create_dir(proc_dir.path().join("self")).unwrap();
+        proc_dir
+    }
+
+    #[test]
+    fn parses_stat_with_spaces_in_name() {
+        assert_eq!(
+            parse_stat("42 (my (odd) proc) Z 1 2 3"),
+            Some(("my (odd) proc", 'Z'))
+        );
+        assert_eq!(parse_stat("garbage"), None);
+    }
+
+    #[tokio::test]
+    async fn process_by_name_found_returns_success() {
+        let proc_dir = fixture_proc();
+        let check = ProcessCheck {
+            process_name: Some("systemd".to_string()),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn process_by_name_missing_returns_failure() {
+        let proc_dir = fixture_proc();
+        let check = ProcessCheck {
+            process_name: Some("nginx".to_string()),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("0 matching processes running, minimum is 1")
+        );
+    }
+
+    #[tokio::test]
+    async fn cmdline_pattern_with_instance_counts() {
+        let proc_dir = fixture_proc();
+        let check = ProcessCheck {
+            cmdline_pattern: Some(Regex::new("queue:work").unwrap()),
+            min_count: 2,
+            max_count: Some(2),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+
+        let check = ProcessCheck {
+            cmdline_pattern: Some(Regex::new("queue:work").unwrap()),
+            max_count: Some(1),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("maximum is 1"));
+    }
+
+    #[tokio::test]
+    async fn pidfile_with_running_process_returns_success() {
+        let proc_dir = fixture_proc();
+        let pidfile = proc_dir.path().join("worker.pid");
+        std_fs::write(&pidfile, "100\n").unwrap();
+        let check = ProcessCheck {
+            pidfile_path: Some(pidfile),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn pidfile_with_stale_pid_returns_failure() {
+        let proc_dir = fixture_proc();
+        let pidfile = proc_dir.path().join("worker.pid");
+        std_fs::write(&pidfile, "4242").unwrap();
+        let check = ProcessCheck {
+            pidfile_path: Some(pidfile),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("is not running"));
+    }
+
+    #[tokio::test]
+    async fn missing_pidfile_returns_failure() {
+        let proc_dir = fixture_proc();
+        let check = ProcessCheck {
+            pidfile_path: Some(proc_dir.path().join("missing.pid")),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result
+            .failure_reason
+            .unwrap()
+            .contains("unable to read pidfile"));
+    }
+
+    #[tokio::test]
+    async fn uninterruptible_process_fails_if_enabled() {
+        let proc_dir = fixture_proc();
+        let check = ProcessCheck {
+            process_name: Some("php-fpm".to_string()),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+
+        let check = ProcessCheck {
+            process_name: Some("php-fpm".to_string()),
+            fail_on_uninterruptible: true,
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("process 102 (php-fpm) is in uninterruptible sleep")
+        );
+    }
+
+    #[tokio::test]
+    async fn zombie_process_fails_if_enabled() {
+        let proc_dir = fixture_proc();
+        add_process(&proc_dir, 200, "worker", 'Z', &[]);
+        let check = ProcessCheck {
+            process_name: Some("worker".to_string()),
+            fail_on_zombie: true,
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.unwrap().contains("is a zombie"));
+    }
+
+    #[tokio::test]
+    async fn zombie_process_selected_by_cmdline_fails_if_enabled() {
+        let proc_dir = fixture_proc();
+        add_process(&proc_dir, 200, "queue-worker", 'Z', &[]);
+        let check = ProcessCheck {
+            cmdline_pattern: Some(Regex::new("^queue-worker").unwrap()),
+            fail_on_zombie: true,
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("process 200 (queue-worker) is a zombie")
+        );
+    }
+
+    #[tokio::test]
+    async fn unreadable_process_is_skipped() {
+        let proc_dir = fixture_proc();
+        // a process that exited between listing and reading its files
+        std_fs::create_dir(proc_dir.path().join("300")).unwrap();
+        std_fs::create_dir(proc_dir.path().join("300").join("stat")).unwrap();
+        let check = ProcessCheck {
+            process_name: Some("systemd".to_string()),
+            ..make_check(proc_dir.path().to_path_buf())
+        };
+        let result = check.execute_check().await.unwrap();
+        assert!(result.failure_reason.is_none());
+    }
+}
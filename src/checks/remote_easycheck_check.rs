@@ -0,0 +1,417 @@
+use crate::http_api_routes::STATE_HEADER;
+use crate::options::Options;
+use crate::status::status_checker::{CheckSeverity, StatusCheckResult, StatusChecker};
+use crate::status::status_holder::{FailingCheck, ServiceState};
+use crate::util::tcp_connector::{RealTcpConnector, TcpConnector};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use http_body_util::{BodyExt, Empty, Limited};
+use hyper::body::Bytes;
+use hyper::client::conn::http1::handshake;
+use hyper::header::HOST;
+use hyper::{Request, StatusCode, Uri};
+use hyper_util::rt::TokioIo;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// The default time to wait for the remote easycheck instance to respond.
+const DEFAULT_REMOTE_TIMEOUT_SECONDS: u64 = 5;
+
+/// The maximum size of the response body that is read from the remote instance.
+const MAX_REMOTE_BODY_SIZE: usize = 1024 * 1024;
+
+/// The maximum number of characters of a plain text body that are included in the failure reason.
+const MAX_TEXT_BODY_CHARS: usize = 256;
+
+pub(crate) struct RemoteEasycheckCheck {
+    remote_addr: SocketAddr,
+    host_header_value: String,
+    endpoint: Uri,
+    request_line_target: String,
+    /// The name of the remote instance, used to prefix the remote failures.
+    remote_name: String,
+    timeout: Duration,
+    connector: Box<dyn TcpConnector>,
+}
+
+#[async_trait]
+impl StatusChecker for RemoteEasycheckCheck {
+    fn from_options(options: &Options) -> anyhow::Result<Option<Self>> {
+        match options.remote_check_url.to_owned() {
+            None => Ok(None),
+            Some(endpoint) => {
+                let authority = endpoint.authority().context("invalid remote check url")?;
+                let remote_port = authority.port().map(|port| port.as_u16()).unwrap_or(80);
+                let remote_host = format!("{}:{}", authority.host(), remote_port);
+                let remote_addr = SocketAddr::from_str(&remote_host)
+                    .context("remote check url must contain a ip address")?;
+
+                let request_line_target = endpoint
+                    .path_and_query()
+                    .map(|pq| pq.as_str())
+                    .filter(|pq| pq.starts_with('/'))
+                    .unwrap_or("/")
+                    .to_string();
+                let remote_name = options
+                    .remote_check_name
+                    .to_owned()
+                    .unwrap_or_else(|| authority.as_str().to_string());
+
+                Ok(Some(Self {
+                    remote_addr,
+                    host_header_value: authority.as_str().to_string(),
+                    endpoint,
+                    request_line_target,
+                    remote_name,
+                    timeout: Duration::from_secs(
+                        options
+                            .remote_check_timeout_seconds
+                            .unwrap_or(DEFAULT_REMOTE_TIMEOUT_SECONDS),
+                    ),
+                    connector: Box::new(RealTcpConnector),
+                }))
+            }
+        }
+    }
+
+    fn check_name(&self) -> String {
+        format!(
+            "remote easycheck {} ({})",
+            &self.remote_name, &self.endpoint
+        )
+    }
+
+    fn check_id(&self) -> &str {
+        "remote"
+    }
+
+    async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+        log::debug!("checking remote easycheck {}", &self.endpoint);
+        let (status, state, body) = timeout(self.timeout, async {
+            let remote_stream = self.connector.connect(&self.remote_addr).await?;
+            let (mut sender, connection) = handshake(TokioIo::new(remote_stream)).await?;
+            tokio::spawn(connection);
+
+            let request = Request::builder()
+                .uri(&self.request_line_target)
+                .header(HOST, &self.host_header_value)
+                .body(Empty::<Bytes>::new())?;
+            let response = sender.send_request(request).await?;
+            let status = response.status();
+            let state = response
+                .headers()
+                .get(&STATE_HEADER)
+                .and_then(|state| state.to_str().ok())
+                .map(str::to_string);
+            let body = Limited::new(response.into_body(), MAX_REMOTE_BODY_SIZE)
+                .collect()
+                .await
+                .map_err(|error| anyhow!("unable to read response body: {}", error))?
+                .to_bytes();
+            anyhow::Ok((status, state, body))
+        })
+        .await
+        .context("remote easycheck did not respond in time")??;
+
+        Ok(self.evaluate_response(status, state.as_deref(), &body))
+    }
+}
+
+impl RemoteEasycheckCheck {
+    /// Evaluates the state and the failing checks returned by the remote instance. The
+    /// state is taken from the state header, or derived from the status code if the
+    /// remote does not send it. Maintenance and drain of the remote are passed on as
+    /// warnings, as the remote is intentionally unavailable and not broken.
+    fn evaluate_response(
+        &self,
+        status: StatusCode,
+        state: Option<&str>,
+        body: &[u8],
+    ) -> StatusCheckResult {
+        let remote_state = match state.map(ServiceState::from_str) {
+            Some(Ok(remote_state)) => remote_state,
+            Some(Err(error)) => {
+                log::debug!("ignoring state header of remote: {}", error);
+                Self::state_from_status(status)
+            }
+            None => Self::state_from_status(status),
+        };
+        let (severity, state_reason) = match remote_state {
+            // a healthy remote can still report warnings, which are passed on as warnings
+            ServiceState::Up | ServiceState::Degraded => (CheckSeverity::Warning, None),
+            ServiceState::Drain => (CheckSeverity::Warning, Some("remote is draining")),
+            ServiceState::Maintenance => (CheckSeverity::Warning, Some("remote is in maintenance")),
+            ServiceState::Down => (CheckSeverity::Critical, None),
+        };
+
+        let mut reasons: Vec<String> = state_reason
+            .map(|state_reason| format!("{}: {}", &self.remote_name, state_reason))
+            .into_iter()
+            .collect();
+        let mut perfdata = vec![];
+        match serde_json::from_slice::<Vec<FailingCheck>>(body) {
+            Ok(failing_checks) => {
+                for check in failing_checks {
+                    reasons.push(format!(
+                        "{}: {}: {}",
+                        &self.remote_name, check.check_name, check.failure_reason
+                    ));
+                    perfdata.extend(check.perfdata);
+                }
+            }
+            Err(error) => {
+                // the remote might answer with a plain text or templated body
+                log::debug!("unable to parse failing checks of remote: {}", error);
+                match (remote_state, Self::summarize_text_body(body)) {
+                    (ServiceState::Down, Some(text)) => reasons.push(format!(
+                        "{}: received status {}: {}",
+                        &self.remote_name, status, text
+                    )),
+                    (_, Some(text)) => reasons.push(format!("{}: {}", &self.remote_name, text)),
+                    (_, None) => {}
+                }
+            }
+        }
+
+        if reasons.is_empty() {
+            return match remote_state {
+                ServiceState::Up => StatusCheckResult::new_success(),
+                ServiceState::Down => {
+                    let reason = format!("{}: received status {}", &self.remote_name, status);
+                    StatusCheckResult::new_failure(reason)
+                }
+                _ => {
+                    let reason = format!("{}: remote is {}", &self.remote_name, remote_state);
+                    StatusCheckResult::new_warning(reason)
+                }
+            };
+        }
+        StatusCheckResult::new_with_severity(severity, reasons.join("; ")).with_perfdata(perfdata)
+    }
+
+    /// Derives the state of a remote that does not send the state header from the status code.
+    fn state_from_status(status: StatusCode) -> ServiceState {
+        if status == StatusCode::OK {
+            ServiceState::Up
+        } else {
+            ServiceState::Down
+        }
+    }
+
+    /// Returns the non-empty lines of a text body on a single line, shortened to
+    /// a length that fits into a failure reason.
+    fn summarize_text_body(body: &[u8]) -> Option<String> {
+        let text = std::str::from_utf8(body).ok()?;
+        let text = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if text.is_empty() {
+            return None;
+        }
+        if text.chars().count() > MAX_TEXT_BODY_CHARS {
+            let shortened: String = text.chars().take(MAX_TEXT_BODY_CHARS).collect();
+            return Some(format!("{}...", shortened));
+        }
+        Some(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::tcp_connector::AsyncStream;
+    use std::io;
+    use std::pin::Pin;
+
+    struct MockConnector {
+        stream: tokio::sync::Mutex<Option<Pin<Box<dyn AsyncStream>>>>,
+    }
+
+    #[async_trait]
+    impl TcpConnector for MockConnector {
+        async fn connect(&self, _addr: &SocketAddr) -> io::Result<Pin<Box<dyn AsyncStream>>> {
+            self.stream
+                .lock()
+                .await
+                .take()
+                .ok_or_else(|| io::Error::other("stream already consumed"))
+        }
+    }
+
+    fn make_check(connector: Box<dyn TcpConnector>) -> RemoteEasycheckCheck {
+        let remote_addr: SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        RemoteEasycheckCheck {
+            remote_addr,
+            host_header_value: remote_addr.to_string(),
+            endpoint: format!("http://{}/", remote_addr).parse().unwrap(),
+            request_line_target: "/".to_string(),
+            remote_name: "db-1".to_string(),
+            timeout: Duration::from_secs(5),
+            connector,
+        }
+    }
+
+    /// Spawns a minimal HTTP/1 server that responds with the given status, state header and body.
+    async fn spawn_easycheck_server(
+        server_stream: tokio::io::DuplexStream,
+        status: StatusCode,
+        state: Option<&'static str>,
+        body: &'static str,
+    ) {
+        use http_body_util::Full;
+        use hyper::server::conn::http1::Builder;
+        use hyper::service::service_fn;
+        use hyper::Response;
+
+        let service = service_fn(move |_req: Request<hyper::body::Incoming>| async move {
+            let mut response = Response::builder().status(status);
+            if let Some(state) = state {
+                response = response.header(&STATE_HEADER, state);
+            }
+            Ok::<_, hyper::Error>(response.body(Full::new(Bytes::from(body))).unwrap())
+        });
+
+        let _ = Builder::new()
+            .serve_connection(TokioIo::new(server_stream), service)
+            .await;
+    }
+
+    async fn execute_against(status: StatusCode, body: &'static str) -> StatusCheckResult {
+        execute_against_with_state(status, None, body).await
+    }
+
+    async fn execute_against_with_state(
+        status: StatusCode,
+        state: Option<&'static str>,
+        body: &'static str,
+    ) -> StatusCheckResult {
+        let (client_stream, server_stream) = tokio::io::duplex(8192);
+        tokio::spawn(spawn_easycheck_server(server_stream, status, state, body));
+        let check = make_check(Box::new(MockConnector {
+            stream: tokio::sync::Mutex::new(Some(Box::pin(client_stream))),
+        }));
+        check.execute_check().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn healthy_remote_returns_success() {
+        let result = execute_against(StatusCode::OK, "[]").await;
+        assert!(result.failure_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn remote_failures_are_prefixed_with_remote_name() {
+        let body = r#"[
+            {"check_name": "disk space check /var", "failure_reason": "/var: 1.0 GiB free"},
+            {"check_name": "process check name=mysqld", "failure_reason": "0 processes found",
+             "perfdata": [{"label": "processes", "value": 0.0}]}
+        ]"#;
+        let result = execute_against(StatusCode::SERVICE_UNAVAILABLE, body).await;
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some(
+                "db-1: disk space check /var: /var: 1.0 GiB free; \
+                 db-1: process check name=mysqld: 0 processes found"
+            )
+        );
+        assert_eq!(result.perfdata.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn remote_warnings_are_passed_on_as_warning() {
+        let body =
+            r#"[{"check_name": "memory check", "failure_reason": "low", "severity": "warning"}]"#;
+        let result = execute_against(StatusCode::OK, body).await;
+        assert_eq!(result.severity, CheckSeverity::Warning);
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("db-1: memory check: low")
+        );
+    }
+
+    #[tokio::test]
+    async fn unparsable_body_returns_status_failure() {
+        let result = execute_against(StatusCode::BAD_GATEWAY, "bad gateway").await;
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("db-1: received status 502 Bad Gateway: bad gateway")
+        );
+    }
+
+    #[tokio::test]
+    async fn state_header_takes_precedence_over_status_code() {
+        let result = execute_against_with_state(StatusCode::NO_CONTENT, Some("up"), "").await;
+        assert!(result.failure_reason.is_none());
+
+        let result = execute_against_with_state(StatusCode::OK, Some("down"), "[]").await;
+        assert_eq!(result.severity, CheckSeverity::Critical);
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("db-1: received status 200 OK")
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_maintenance_and_drain_are_passed_on_as_warning() {
+        let body = r#"[{"check_name": "mtc file", "failure_reason": "mtc file exists"}]"#;
+        let result =
+            execute_against_with_state(StatusCode::SERVICE_UNAVAILABLE, Some("maintenance"), body)
+                .await;
+        assert_eq!(result.severity, CheckSeverity::Warning);
+        assert!(!result.maintenance);
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("db-1: remote is in maintenance; db-1: mtc file: mtc file exists")
+        );
+
+        let result =
+            execute_against_with_state(StatusCode::SERVICE_UNAVAILABLE, Some("drain"), "[]").await;
+        assert_eq!(result.severity, CheckSeverity::Warning);
+        assert!(!result.drain);
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("db-1: remote is draining")
+        );
+    }
+
+    #[tokio::test]
+    async fn text_bodies_are_included_in_failure_reason() {
+        let result = execute_against_with_state(
+            StatusCode::OK,
+            Some("degraded"),
+            "memory check: low\n\nprocess check: ok\n",
+        )
+        .await;
+        assert_eq!(result.severity, CheckSeverity::Warning);
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("db-1: memory check: low, process check: ok")
+        );
+
+        let result = execute_against_with_state(StatusCode::OK, Some("degraded"), "").await;
+        assert_eq!(
+            result.failure_reason.as_deref(),
+            Some("db-1: remote is degraded")
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_remote_returns_error() {
+        struct FailingConnector;
+
+        #[async_trait]
+        impl TcpConnector for FailingConnector {
+            async fn connect(&self, _addr: &SocketAddr) -> io::Result<Pin<Box<dyn AsyncStream>>> {
+                Err(io::Error::from(io::ErrorKind::ConnectionRefused))
+            }
+        }
+
+        let check = make_check(Box::new(FailingConnector));
+        assert!(check.execute_check().await.is_err());
+    }
+}
@@ -1,18 +1,55 @@
+use axum::extract::Query;
 use axum::http::header::AGE;
-use axum::response::IntoResponse;
-use axum::{Extension, Json};
+use axum::http::{HeaderName, StatusCode};
+use axum::response::{AppendHeaders, IntoResponse, Response};
+use axum::Extension;
+use serde::Deserialize;
 
+use crate::status::status_endpoint::StatusEndpoint;
 use crate::status::status_holder::StatusHolder;
+use crate::status_response::StatusResponseSettings;
+
+/// The header containing the highest severity of all failing checks.
+static SEVERITY_HEADER: HeaderName = HeaderName::from_static("x-easycheck-severity");
+
+/// The header containing the state of the service.
+pub(crate) static STATE_HEADER: HeaderName = HeaderName::from_static("x-easycheck-state");
+
+/// The query parameters accepted by the status endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct StatusQuery {
+    /// The comma separated ids of the checks to derive the status from.
+    include: Option<String>,
+    /// The comma separated ids of the checks to ignore.
+    exclude: Option<String>,
+    /// If present, the status of all checks is listed in the response.
+    verbose: Option<String>,
+}
 
 pub(crate) async fn get_status(
     Extension(status_holder): Extension<StatusHolder>,
-) -> impl IntoResponse {
-    let current_status = status_holder.current_status().await;
+    Extension(endpoint): Extension<StatusEndpoint>,
+    Extension(response_settings): Extension<StatusResponseSettings>,
+    Query(query): Query<StatusQuery>,
+) -> Response {
+    let check_ids = match endpoint.select_checks(query.include.as_deref(), query.exclude.as_deref())
+    {
+        Ok(check_ids) => check_ids,
+        Err(error) => return (StatusCode::BAD_REQUEST, error).into_response(),
+    };
+    let current_status = status_holder.current_status_of(Some(&check_ids)).await;
     let status_checks_age = current_status.timestamp.elapsed().as_secs();
+    let response_code = endpoint
+        .response_settings
+        .status_code(current_status.state, &response_settings);
+    let headers = [
+        (AGE, status_checks_age.to_string()),
+        (SEVERITY_HEADER.clone(), current_status.severity.to_string()),
+        (STATE_HEADER.clone(), current_status.state.to_string()),
+    ];
+    let extra_headers = AppendHeaders(response_settings.extra_headers(current_status.state));
+    let body =
+        response_settings.render_body(current_status, response_code, query.verbose.is_some());
 
-    (
-        current_status.api_response_code,
-        [(AGE, status_checks_age.to_string())],
-        Json(current_status.failing_checks),
-    )
+    (response_code, headers, extra_headers, body).into_response()
 }
@@ -8,14 +8,21 @@ use clap::Parser;
 use tokio::net::TcpListener;
 use tokio::time;
 
+use crate::admin_api::AdminApiSettings;
 use crate::http_api_routes::get_status;
 use crate::options::Options;
 use crate::status::status_manager::StatusManager;
+use crate::status::status_watchdog::watch_check_cycles;
+use crate::status_response::StatusResponseSettings;
+use crate::util::file_watcher::{FileWatcher, WatchedPath};
 
+mod admin_api;
 pub(crate) mod checks;
 mod http_api_routes;
+mod maintenance_command;
 pub(crate) mod options;
 pub(crate) mod status;
+mod status_response;
 pub(crate) mod util;
 
 #[tokio::main]
@@ -24,6 +31,16 @@ async fn main() -> anyhow::Result<()> {
 
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    if let Some(command) = &options.command {
+        if let Err(error) = command.execute(&options).await {
+            eprintln!("error: {:#}", error);
+            exit(1)
+        }
+        return Ok(());
+    }
+    // the bind host is required by clap unless a subcommand is given
+    let bind_host = options.bind_host.clone().unwrap_or_default();
+
     let status_manager = match StatusManager::from_options(&options) {
         Ok(manager) => manager,
         Err(error) => {
@@ -34,26 +51,98 @@ async fn main() -> anyhow::Result<()> {
             exit(1)
         }
     };
+    let response_settings = match StatusResponseSettings::from_options(&options) {
+        Ok(settings) => settings,
+        Err(error) => {
+            log::error!(
+                "Unable to construct response settings based on provided options: {}",
+                error
+            );
+            exit(1)
+        }
+    };
+    let admin_api_settings = match AdminApiSettings::from_options(&options) {
+        Ok(settings) => settings,
+        Err(error) => {
+            log::error!(
+                "Unable to construct admin api settings based on provided options: {:#}",
+                error
+            );
+            exit(1)
+        }
+    };
     let axum_status_holder = status_manager.status_holder();
+    let status_endpoints = status_manager.status_endpoints();
 
     let revalidation_interval = Duration::from_secs(options.revalidate_interval_seconds);
+    tokio::spawn(watch_check_cycles(
+        status_manager.status_holder(),
+        revalidation_interval,
+    ));
+    // changes of the watched files (like the maintenance file) immediately trigger a
+    // check cycle, if the files cannot be watched they are only checked periodically
+    let mut file_watcher = match FileWatcher::new(&status_manager.watched_paths()) {
+        Ok(file_watcher) => Some(file_watcher),
+        Err(error) => {
+            log::warn!(
+                "Unable to watch files, changes are only detected by the check cycles: {}",
+                error
+            );
+            None
+        }
+    };
     let status_updating_task = tokio::spawn(async move {
         loop {
             status_manager.execute_status_checks().await;
-            time::sleep(revalidation_interval).await;
+            if let Some(file_watcher) = &mut file_watcher {
+                file_watcher.rewatch();
+            }
+            let next_cycle = time::Instant::now() + revalidation_interval;
+            while let Some(changed_paths) = wait_for_changes(next_cycle, &mut file_watcher).await {
+                status_manager.execute_checks_watching(&changed_paths).await;
+            }
         }
     });
 
-    let app = Router::new()
-        .route("/", get(get_status).options(get_status))
-        .layer(Extension(axum_status_holder));
-    let listener = TcpListener::bind(&options.bind_host).await?;
+    let mut app = Router::new();
+    for endpoint in status_endpoints {
+        let path = endpoint.path.clone();
+        let endpoint_route = get(get_status)
+            .options(get_status)
+            .layer(Extension(endpoint));
+        app = app.route(&path, endpoint_route);
+    }
+    let mut app = app
+        .layer(Extension(axum_status_holder))
+        .layer(Extension(response_settings));
+
+    // the admin api is served on its own bind address if configured, else
+    // alongside the status endpoints
+    let mut admin_serve_future = None;
+    if let Some(admin_api_settings) = admin_api_settings {
+        match &options.admin_bind_host {
+            Some(admin_bind_host) => {
+                let admin_listener = TcpListener::bind(admin_bind_host).await?;
+                let admin_app = admin_api_settings.router();
+                admin_serve_future = Some(axum::serve(admin_listener, admin_app).into_future());
+                eprintln!("easycheck admin api listening on {}", admin_bind_host);
+            }
+            None => app = app.merge(admin_api_settings.router()),
+        }
+    }
+    let listener = TcpListener::bind(&bind_host).await?;
     let axum_serve_future = axum::serve(listener, app).into_future();
     eprintln!(
         "easycheck v{} listening on {}",
         env!("CARGO_PKG_VERSION"),
-        &options.bind_host
+        &bind_host
     );
+    let admin_serve_future = async {
+        match admin_serve_future {
+            Some(admin_serve_future) => admin_serve_future.await,
+            None => std::future::pending().await,
+        }
+    };
 
     let exit_code = tokio::select! {
         _ = status_updating_task => {
@@ -64,6 +153,10 @@ async fn main() -> anyhow::Result<()> {
             log::error!("Serving http endpoint failed");
             101
         }
+        _ = admin_serve_future => {
+            log::error!("Serving admin http endpoint failed");
+            102
+        }
         _ = tokio::signal::ctrl_c() => {
             log::info!("Quit signal received, exiting!");
             0
@@ -72,3 +165,31 @@ async fn main() -> anyhow::Result<()> {
 
     exit(exit_code)
 }
+
+/// Waits until one of the watched files changed before the next check cycle is due,
+/// returns the changed paths or `None` once the next check cycle is due.
+async fn wait_for_changes(
+    next_cycle: time::Instant,
+    file_watcher: &mut Option<FileWatcher>,
+) -> Option<Vec<WatchedPath>> {
+    let Some(watcher) = file_watcher else {
+        time::sleep_until(next_cycle).await;
+        return None;
+    };
+    let watch_result = tokio::select! {
+        _ = time::sleep_until(next_cycle) => return None,
+        result = watcher.changed() => result,
+    };
+    match watch_result {
+        Ok(changed_paths) => Some(changed_paths),
+        Err(error) => {
+            log::warn!(
+                "Watching files failed, changes are only detected by the check cycles: {}",
+                error
+            );
+            *file_watcher = None;
+            time::sleep_until(next_cycle).await;
+            None
+        }
+    }
+}
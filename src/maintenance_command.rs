@@ -0,0 +1,459 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use clap::{Args, Subcommand};
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::Bytes;
+use hyper::client::conn::http1::handshake;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, HOST};
+use hyper::{HeaderMap, Method, Request, StatusCode, Uri};
+use hyper_util::rt::TokioIo;
+use jiff::{SignedDuration, Timestamp};
+use tokio::fs;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Instant};
+
+use crate::admin_api::{read_admin_tokens, EnableRequest, ToggleStatus};
+use crate::checks::mtc_file_check::DEFAULT_MTC_FILE_PATH;
+use crate::http_api_routes::STATE_HEADER;
+use crate::options::Options;
+use crate::util::maintenance_info::{
+    read_maintenance_file, write_maintenance_file, MaintenanceInfo,
+};
+
+/// The time to wait for a response of the running instance.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum size of the response body that is read from the running instance.
+const MAX_RESPONSE_BODY_SIZE: usize = 1024 * 1024;
+
+/// The interval in which the status endpoint is polled while waiting for the new state.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The state reported by the status endpoint while the instance is in maintenance.
+const MAINTENANCE_STATE: &str = "maintenance";
+
+/// The subcommands of easycheck, which are executed instead of serving the status endpoints.
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum Command {
+    /// Manages the maintenance of an easycheck instance, either by changing the
+    /// maintenance file directly or through the admin api of the running instance.
+    Maintenance {
+        #[command(subcommand)]
+        action: MaintenanceAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum MaintenanceAction {
+    /// Puts the instance into maintenance.
+    On(MaintenanceOnArgs),
+    /// Ends the maintenance of the instance.
+    Off(MaintenanceOffArgs),
+    /// Shows the current maintenance of the instance.
+    Status(AdminTargetArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct MaintenanceOnArgs {
+    /// The reason of the maintenance.
+    #[arg(long = "reason")]
+    pub reason: Option<String>,
+    /// The author of the maintenance, defaults to the current user.
+    #[arg(long = "by")]
+    pub by: Option<String>,
+    /// The duration after which the maintenance ends (e.g. `30m` or `2h`).
+    #[arg(long = "for", value_parser = parse_maintenance_duration)]
+    pub duration: Option<Duration>,
+    #[command(flatten)]
+    pub target: AdminTargetArgs,
+    #[command(flatten)]
+    pub wait: WaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct MaintenanceOffArgs {
+    #[command(flatten)]
+    pub target: AdminTargetArgs,
+    #[command(flatten)]
+    pub wait: WaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct AdminTargetArgs {
+    /// The url of the admin api of the running instance (e.g. `http://127.0.0.1:8081`).
+    /// The token is read from the admin token file. If not given, the maintenance
+    /// file is changed directly.
+    #[arg(long = "admin-url", env = "EASYCHECK_ADMIN_URL")]
+    pub admin_url: Option<Uri>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct WaitArgs {
+    /// Waits until the status endpoint reports the new state.
+    #[arg(long = "wait")]
+    pub wait: bool,
+    /// The url of the status endpoint to wait for. Defaults to the root endpoint on the bind host.
+    #[arg(long = "status-url", env = "EASYCHECK_STATUS_URL")]
+    pub status_url: Option<Uri>,
+    /// The maximum time to wait for the new state in seconds.
+    #[arg(long = "wait-timeout", default_value_t = 60)]
+    pub wait_timeout_seconds: u64,
+}
+
+/// Parses a maintenance duration of at least one second like `30m` or `1h 30m`,
+/// as the admin api only accepts whole seconds.
+fn parse_maintenance_duration(value: &str) -> Result<Duration, String> {
+    let duration = value
+        .parse::<SignedDuration>()
+        .map_err(|error| format!("invalid duration {}: {}", value, error))?;
+    match Duration::try_from(duration) {
+        Ok(duration) if duration >= Duration::from_secs(1) => Ok(duration),
+        _ => Err(format!("duration {} must be at least one second", value)),
+    }
+}
+
+impl Command {
+    /// Executes this command based on the given options.
+    pub async fn execute(&self, options: &Options) -> anyhow::Result<()> {
+        match self {
+            Command::Maintenance { action } => action.execute(options).await,
+        }
+    }
+}
+
+impl MaintenanceAction {
+    async fn execute(&self, options: &Options) -> anyhow::Result<()> {
+        let status = match self {
+            MaintenanceAction::On(args) => {
+                let status = enable_maintenance(options, args).await?;
+                args.wait.wait_for_state(options, true).await?;
+                status
+            }
+            MaintenanceAction::Off(args) => {
+                let status = disable_maintenance(options, &args.target).await?;
+                args.wait.wait_for_state(options, false).await?;
+                status
+            }
+            MaintenanceAction::Status(target) => maintenance_status(options, target).await?,
+        };
+        println!("{}", describe_status(&status));
+        Ok(())
+    }
+}
+
+impl WaitArgs {
+    /// Waits until the status endpoint reports that the instance is in maintenance or
+    /// not anymore, if waiting is requested.
+    async fn wait_for_state(&self, options: &Options, maintenance: bool) -> anyhow::Result<()> {
+        if !self.wait {
+            return Ok(());
+        }
+        let status_url = match &self.status_url {
+            Some(status_url) => status_url.clone(),
+            None => default_status_url(options)?,
+        };
+        let deadline = Instant::now() + Duration::from_secs(self.wait_timeout_seconds);
+        loop {
+            match send_request(Method::GET, &status_url, None, None).await {
+                Ok((_, headers, _)) => {
+                    let state = headers
+                        .get(&STATE_HEADER)
+                        .and_then(|state| state.to_str().ok())
+                        .unwrap_or_default();
+                    if (state == MAINTENANCE_STATE) == maintenance {
+                        return Ok(());
+                    }
+                    log::debug!("status endpoint reports state {}", state);
+                }
+                Err(error) => log::debug!("unable to query status endpoint: {:#}", error),
+            }
+            if Instant::now() > deadline {
+                bail!(
+                    "status endpoint {} did not report the new state within {}s",
+                    status_url,
+                    self.wait_timeout_seconds
+                );
+            }
+            sleep(WAIT_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Returns the url of the root status endpoint on the configured bind host. Unspecified
+/// bind addresses are replaced by the loopback address.
+fn default_status_url(options: &Options) -> anyhow::Result<Uri> {
+    let bind_host = options
+        .bind_host
+        .as_deref()
+        .context("waiting for the new state requires a status url or bind host")?;
+    let bind_host = bind_host
+        .replace("0.0.0.0", "127.0.0.1")
+        .replace("[::]", "[::1]");
+    format!("http://{}/", bind_host)
+        .parse()
+        .with_context(|| format!("invalid bind host {}", bind_host))
+}
+
+fn mtc_file_path(options: &Options) -> PathBuf {
+    PathBuf::from(
+        options
+            .mtc_check_file_path
+            .as_deref()
+            .unwrap_or(DEFAULT_MTC_FILE_PATH),
+    )
+}
+
+async fn enable_maintenance(
+    options: &Options,
+    args: &MaintenanceOnArgs,
+) -> anyhow::Result<ToggleStatus> {
+    let by = args.by.clone().or_else(|| std::env::var("USER").ok());
+    if let Some(admin_url) = &args.target.admin_url {
+        let request = EnableRequest {
+            reason: args.reason.clone(),
+            by,
+            // fractions of a second are rounded up, so that the maintenance does not end early
+            ttl: args
+                .duration
+                .map(|duration| duration.as_secs() + u64::from(duration.subsec_nanos() > 0)),
+        };
+        return send_admin_request(options, admin_url, Method::POST, Some(&request)).await;
+    }
+
+    let now = Timestamp::now();
+    let until = args
+        .duration
+        .map(|duration| now.checked_add(duration))
+        .transpose()
+        .context("maintenance duration is too long")?;
+    let info = MaintenanceInfo {
+        reason: args.reason.clone(),
+        by,
+        since: Some(now.to_string()),
+        until: until.map(|until| until.to_string()),
+    };
+    let file_path = mtc_file_path(options);
+    write_maintenance_file(&file_path, &info)
+        .await
+        .with_context(|| format!("unable to write maintenance file {:?}", file_path))?;
+    Ok(ToggleStatus {
+        enabled: true,
+        info,
+    })
+}
+
+async fn disable_maintenance(
+    options: &Options,
+    target: &AdminTargetArgs,
+) -> anyhow::Result<ToggleStatus> {
+    if let Some(admin_url) = &target.admin_url {
+        return send_admin_request(options, admin_url, Method::DELETE, None).await;
+    }
+
+    let file_path = mtc_file_path(options);
+    match fs::remove_file(&file_path).await {
+        Ok(()) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => {
+            return Err(error)
+                .with_context(|| format!("unable to remove maintenance file {:?}", file_path))
+        }
+    }
+    Ok(ToggleStatus {
+        enabled: false,
+        info: MaintenanceInfo::default(),
+    })
+}
+
+async fn maintenance_status(
+    options: &Options,
+    target: &AdminTargetArgs,
+) -> anyhow::Result<ToggleStatus> {
+    if let Some(admin_url) = &target.admin_url {
+        return send_admin_request(options, admin_url, Method::GET, None).await;
+    }
+
+    let file_path = mtc_file_path(options);
+    let info = read_maintenance_file(&file_path)
+        .await
+        .with_context(|| format!("unable to read maintenance file {:?}", file_path))?;
+    Ok(match info {
+        Some(info) if !info.has_ended() => ToggleStatus {
+            enabled: true,
+            info,
+        },
+        _ => ToggleStatus {
+            enabled: false,
+            info: MaintenanceInfo::default(),
+        },
+    })
+}
+
+fn describe_status(status: &ToggleStatus) -> String {
+    match (status.enabled, status.info.describe()) {
+        (false, _) => String::from("maintenance off"),
+        (true, None) => String::from("maintenance on"),
+        (true, Some(description)) => format!("maintenance on: {}", description),
+    }
+}
+
+/// Sends the given request to the maintenance endpoint of the admin api, authenticated
+/// with the first token of the admin token file.
+async fn send_admin_request(
+    options: &Options,
+    admin_url: &Uri,
+    method: Method,
+    request: Option<&EnableRequest>,
+) -> anyhow::Result<ToggleStatus> {
+    let token_file_path = options
+        .admin_token_file_path
+        .as_deref()
+        .context("the admin api requires an admin token file")?;
+    let token = read_admin_tokens(token_file_path)?.swap_remove(0);
+    let path = admin_url.path().trim_end_matches('/');
+    let url = Uri::builder()
+        .scheme(admin_url.scheme_str().unwrap_or("http"))
+        .authority(
+            admin_url
+                .authority()
+                .context("admin url must contain a host")?
+                .as_str(),
+        )
+        .path_and_query(format!("{}/admin/maintenance", path))
+        .build()?;
+    let body = request.map(serde_json::to_vec).transpose()?;
+
+    let (status, _, body) = send_request(method, &url, Some(&token), body).await?;
+    if status != StatusCode::OK {
+        bail!(
+            "admin api responded with status {}: {}",
+            status,
+            String::from_utf8_lossy(&body)
+        );
+    }
+    serde_json::from_slice(&body).context("unable to parse response of admin api")
+}
+
+/// Sends a http request to the given url, optionally authenticated with the given
+/// bearer token and with the given json body.
+async fn send_request(
+    method: Method,
+    url: &Uri,
+    token: Option<&str>,
+    body: Option<Vec<u8>>,
+) -> anyhow::Result<(StatusCode, HeaderMap, Bytes)> {
+    if url.scheme_str().is_some_and(|scheme| scheme != "http") {
+        bail!("only http urls are supported: {}", url);
+    }
+    let authority = url.authority().context("url must contain a host")?;
+    let host = authority
+        .host()
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = authority.port_u16().unwrap_or(80);
+
+    timeout(REQUEST_TIMEOUT, async {
+        let stream = TcpStream::connect((host, port)).await?;
+        let (mut sender, connection) = handshake(TokioIo::new(stream)).await?;
+        tokio::spawn(connection);
+
+        let mut request = Request::builder()
+            .method(method)
+            .uri(url.path_and_query().map_or("/", |path| path.as_str()))
+            .header(HOST, authority.as_str());
+        if let Some(token) = token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        if body.is_some() {
+            request = request.header(CONTENT_TYPE, "application/json");
+        }
+        let request = request.body(Full::new(Bytes::from(body.unwrap_or_default())))?;
+        let response = sender.send_request(request).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = Limited::new(response.into_body(), MAX_RESPONSE_BODY_SIZE)
+            .collect()
+            .await
+            .map_err(|error| anyhow!("unable to read response body: {}", error))?
+            .to_bytes();
+        anyhow::Ok((status, headers, body))
+    })
+    .await
+    .with_context(|| format!("{} did not respond in time", url))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn parses_maintenance_duration() {
+        assert_eq!(
+            parse_maintenance_duration("30m"),
+            Ok(Duration::from_secs(30 * 60))
+        );
+        assert_eq!(
+            parse_maintenance_duration("1h 30m"),
+            Ok(Duration::from_secs(90 * 60))
+        );
+        assert!(parse_maintenance_duration("0s").is_err());
+        assert!(parse_maintenance_duration("500ms").is_err());
+        assert_eq!(
+            parse_maintenance_duration("1.5s"),
+            Ok(Duration::from_millis(1500))
+        );
+        assert!(parse_maintenance_duration("-5m").is_err());
+        assert!(parse_maintenance_duration("soon").is_err());
+    }
+
+    #[test]
+    fn parses_maintenance_command_without_bind() {
+        let options = Options::try_parse_from([
+            "easycheck",
+            "maintenance",
+            "on",
+            "--reason",
+            "kernel update",
+            "--for",
+            "30m",
+        ])
+        .unwrap();
+        let Some(Command::Maintenance {
+            action: MaintenanceAction::On(args),
+        }) = options.command
+        else {
+            panic!("unexpected command {:?}", options.command);
+        };
+        assert_eq!(args.reason.as_deref(), Some("kernel update"));
+        assert_eq!(args.duration, Some(Duration::from_secs(30 * 60)));
+        assert!(Options::try_parse_from(["easycheck"]).is_err());
+    }
+
+    #[test]
+    fn derives_status_url_from_bind_host() {
+        let options = Options::try_parse_from(["easycheck", "--bind", "0.0.0.0:8080"]).unwrap();
+        assert_eq!(
+            default_status_url(&options).unwrap(),
+            "http://127.0.0.1:8080/"
+        );
+    }
+
+    #[test]
+    fn describes_maintenance_status() {
+        let status = ToggleStatus {
+            enabled: true,
+            info: MaintenanceInfo {
+                reason: Some("kernel update".to_string()),
+                by: Some("jane".to_string()),
+                ..MaintenanceInfo::default()
+            },
+        };
+        assert_eq!(
+            describe_status(&status),
+            "maintenance on: kernel update (by jane)"
+        );
+    }
+}
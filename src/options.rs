@@ -1,9 +1,19 @@
 use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
 
 use axum::http::Method;
 use clap::{Parser, ValueEnum};
 use hyper::{StatusCode, Uri};
 
+use crate::checks::pressure_check::PressureThreshold;
+use crate::maintenance_command::Command;
+use crate::status::check_group::GroupPolicy;
+use crate::status::check_settings::{CheckIds, CheckInitialState, CheckSetting};
+use crate::status::status_checker::CheckSeverity;
+use crate::status_response::{BodyFormat, ResponseHeader};
+use crate::util::threshold::Threshold;
+
 #[derive(ValueEnum, Debug, Clone, Eq, PartialEq)]
 pub enum ProxyProtocolVersion {
     V1,
@@ -11,25 +21,153 @@ pub enum ProxyProtocolVersion {
 }
 
 #[derive(Parser, Debug, Clone)]
-#[command(version)]
+#[command(version, subcommand_negates_reqs = true)]
 pub(crate) struct Options {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     #[arg(long = "bind", env = "EASYCHECK_BIND_HOST", required = true)]
-    pub bind_host: String,
+    pub bind_host: Option<String>,
+    // admin api options
+    #[arg(long = "admin-token-file", env = "EASYCHECK_ADMIN_TOKEN_FILE")]
+    pub admin_token_file_path: Option<String>,
+    #[arg(long = "admin-bind", env = "EASYCHECK_ADMIN_BIND_HOST")]
+    pub admin_bind_host: Option<String>,
     #[arg(
         long = "revalidation-interval",
         env = "EASYCHECK_REVALIDATE_INTERVAL",
         default_value_t = 5
     )]
     pub revalidate_interval_seconds: u64,
+    #[arg(
+        long = "check-severity",
+        env = "EASYCHECK_CHECK_SEVERITY",
+        value_delimiter = ','
+    )]
+    pub check_severity: Option<Vec<CheckSetting<CheckSeverity>>>,
+    #[arg(
+        long = "check-rise",
+        env = "EASYCHECK_CHECK_RISE",
+        value_delimiter = ','
+    )]
+    pub check_rise: Option<Vec<CheckSetting<NonZeroU32>>>,
+    #[arg(
+        long = "check-fall",
+        env = "EASYCHECK_CHECK_FALL",
+        value_delimiter = ','
+    )]
+    pub check_fall: Option<Vec<CheckSetting<NonZeroU32>>>,
+    #[arg(
+        long = "check-initial-state",
+        env = "EASYCHECK_CHECK_INITIAL_STATE",
+        value_delimiter = ','
+    )]
+    pub check_initial_state: Option<Vec<CheckSetting<CheckInitialState>>>,
+    #[arg(
+        long = "check-depends-on",
+        env = "EASYCHECK_CHECK_DEPENDS_ON",
+        value_delimiter = ','
+    )]
+    pub check_depends_on: Option<Vec<CheckSetting<String>>>,
+    #[arg(
+        long = "check-drain",
+        env = "EASYCHECK_CHECK_DRAIN",
+        value_delimiter = ','
+    )]
+    pub check_drain: Option<Vec<String>>,
+    #[arg(
+        long = "startup-grace-period",
+        env = "EASYCHECK_STARTUP_GRACE_PERIOD",
+        default_value_t = 0
+    )]
+    pub startup_grace_period_seconds: u64,
+    #[arg(long = "max-result-age", env = "EASYCHECK_MAX_RESULT_AGE")]
+    pub max_result_age_seconds: Option<u64>,
+    #[arg(
+        long = "check-group",
+        env = "EASYCHECK_CHECK_GROUP",
+        value_delimiter = ','
+    )]
+    pub check_groups: Option<Vec<CheckSetting<CheckIds>>>,
+    #[arg(
+        long = "check-group-policy",
+        env = "EASYCHECK_CHECK_GROUP_POLICY",
+        value_delimiter = ','
+    )]
+    pub check_group_policy: Option<Vec<CheckSetting<GroupPolicy>>>,
+    #[arg(
+        long = "up-status-code",
+        env = "EASYCHECK_UP_STATUS_CODE",
+        default_value = "200"
+    )]
+    pub up_status_code: StatusCode,
+    #[arg(
+        long = "degraded-status-code",
+        env = "EASYCHECK_DEGRADED_STATUS_CODE",
+        default_value = "200"
+    )]
+    pub degraded_status_code: StatusCode,
+    #[arg(
+        long = "maintenance-status-code",
+        env = "EASYCHECK_MAINTENANCE_STATUS_CODE",
+        default_value = "503"
+    )]
+    pub maintenance_status_code: StatusCode,
+    #[arg(
+        long = "drain-status-code",
+        env = "EASYCHECK_DRAIN_STATUS_CODE",
+        default_value = "404"
+    )]
+    pub drain_status_code: StatusCode,
+    #[arg(
+        long = "down-status-code",
+        env = "EASYCHECK_DOWN_STATUS_CODE",
+        default_value = "503"
+    )]
+    pub down_status_code: StatusCode,
+    #[arg(
+        long = "body-format",
+        env = "EASYCHECK_BODY_FORMAT",
+        default_value = "json"
+    )]
+    pub body_format: BodyFormat,
+    #[arg(long = "body-template", env = "EASYCHECK_BODY_TEMPLATE")]
+    pub body_template: Option<String>,
+    #[arg(long = "response-header", env = "EASYCHECK_RESPONSE_HEADER")]
+    pub response_headers: Option<Vec<ResponseHeader>>,
+    #[arg(
+        long = "drain-response-header",
+        env = "EASYCHECK_DRAIN_RESPONSE_HEADER"
+    )]
+    pub drain_response_headers: Option<Vec<ResponseHeader>>,
+    #[arg(long = "endpoint", env = "EASYCHECK_ENDPOINT", value_delimiter = ',')]
+    pub status_endpoints: Option<Vec<CheckSetting<CheckIds>>>,
+    #[arg(
+        long = "endpoint-status-code",
+        env = "EASYCHECK_ENDPOINT_STATUS_CODE",
+        value_delimiter = ','
+    )]
+    pub endpoint_status_codes: Option<Vec<CheckSetting<StatusCode>>>,
     // file path for force success check
     #[arg(
         long = "force-success-file-path",
         env = "EASYCHECK_FORCE_SUCCESS_FILE_PATH"
     )]
     pub force_success_file_path: Option<String>,
+    // directory path for force success check, each file inside forces success
+    #[arg(
+        long = "force-success-dir-path",
+        env = "EASYCHECK_FORCE_SUCCESS_DIR_PATH"
+    )]
+    pub force_success_dir_path: Option<String>,
     // file path for mtc check
     #[arg(long = "mtc-file-path", env = "EASYCHECK_MTC_FILE_PATH")]
     pub mtc_check_file_path: Option<String>,
+    // directory path for mtc check, each file inside is a separate maintenance hold
+    #[arg(long = "mtc-dir-path", env = "EASYCHECK_MTC_DIR_PATH")]
+    pub mtc_check_dir_path: Option<String>,
+    // file path for drain check
+    #[arg(long = "drain-file-path", env = "EASYCHECK_DRAIN_FILE_PATH")]
+    pub drain_file_path: Option<String>,
     // check options for plain sockets
     #[arg(long = "socket-addr", env = "EASYCHECK_SOCKET_ADDR")]
     pub socket_check_addr: Option<SocketAddr>,
@@ -38,9 +176,23 @@ pub(crate) struct Options {
         env = "EASYCHECK_READ_INITIAL_RESPONSE"
     )]
     pub socket_check_read_initial_response: Option<bool>,
+    #[arg(
+        long = "socket-proxy-protocol-version",
+        env = "EASYCHECK_SOCKET_PROXY_PROTOCOL_VERSION"
+    )]
+    pub socket_proxy_protocol_version: Option<ProxyProtocolVersion>,
+    #[arg(long = "socket-tls", env = "EASYCHECK_SOCKET_TLS")]
+    pub socket_check_tls: Option<bool>,
+    #[arg(
+        long = "socket-tls-server-name",
+        env = "EASYCHECK_SOCKET_TLS_SERVER_NAME"
+    )]
+    pub socket_check_tls_server_name: Option<String>,
+    #[arg(long = "socket-tls-ca-file", env = "EASYCHECK_SOCKET_TLS_CA_FILE")]
+    pub socket_check_tls_ca_file_path: Option<String>,
     // check options for http checks
     #[arg(long = "http-url", env = "EASYCHECK_HTTP_URL")]
-    pub http_check_url: Option<Uri>,
+    pub http_check_url: Option<Vec<Uri>>,
     #[arg(long = "http-method", env = "EASYCHECK_HTTP_METHOD")]
     pub http_check_method: Option<Method>,
     #[arg(long = "http-status-codes", env = "EASYCHECK_HTTP_STATUS_CODES")]
@@ -50,4 +202,162 @@ pub(crate) struct Options {
         env = "EASYCHECK_HTTP_PROXY_PROTOCOL_VERSION"
     )]
     pub http_proxy_protocol_version: Option<ProxyProtocolVersion>,
+    // header options for the proxy protocol (shared between http and socket checks)
+    #[arg(
+        long = "proxy-protocol-source",
+        env = "EASYCHECK_PROXY_PROTOCOL_SOURCE"
+    )]
+    pub proxy_protocol_source_addr: Option<SocketAddr>,
+    #[arg(
+        long = "proxy-protocol-destination",
+        env = "EASYCHECK_PROXY_PROTOCOL_DESTINATION"
+    )]
+    pub proxy_protocol_destination_addr: Option<SocketAddr>,
+    #[arg(long = "proxy-protocol-local", env = "EASYCHECK_PROXY_PROTOCOL_LOCAL")]
+    pub proxy_protocol_local: Option<bool>,
+    #[arg(long = "proxy-protocol-alpn", env = "EASYCHECK_PROXY_PROTOCOL_ALPN")]
+    pub proxy_protocol_alpn: Option<String>,
+    #[arg(
+        long = "proxy-protocol-authority",
+        env = "EASYCHECK_PROXY_PROTOCOL_AUTHORITY"
+    )]
+    pub proxy_protocol_authority: Option<String>,
+    #[arg(
+        long = "proxy-protocol-unique-id",
+        env = "EASYCHECK_PROXY_PROTOCOL_UNIQUE_ID"
+    )]
+    pub proxy_protocol_unique_id: Option<String>,
+    #[arg(
+        long = "proxy-protocol-ssl-version",
+        env = "EASYCHECK_PROXY_PROTOCOL_SSL_VERSION"
+    )]
+    pub proxy_protocol_ssl_version: Option<String>,
+    #[arg(
+        long = "proxy-protocol-ssl-cn",
+        env = "EASYCHECK_PROXY_PROTOCOL_SSL_CN"
+    )]
+    pub proxy_protocol_ssl_cn: Option<String>,
+    // check options for disk space checks
+    #[arg(long = "disk-path", env = "EASYCHECK_DISK_PATH", value_delimiter = ',')]
+    pub disk_check_paths: Option<Vec<PathBuf>>,
+    #[arg(long = "disk-min-free", env = "EASYCHECK_DISK_MIN_FREE")]
+    pub disk_check_min_free_space: Option<Threshold>,
+    #[arg(long = "disk-warn-free", env = "EASYCHECK_DISK_WARN_FREE")]
+    pub disk_check_warn_free_space: Option<Threshold>,
+    #[arg(long = "disk-min-free-inodes", env = "EASYCHECK_DISK_MIN_FREE_INODES")]
+    pub disk_check_min_free_inodes: Option<Threshold>,
+    #[arg(
+        long = "disk-warn-free-inodes",
+        env = "EASYCHECK_DISK_WARN_FREE_INODES"
+    )]
+    pub disk_check_warn_free_inodes: Option<Threshold>,
+    // check options for memory and swap checks
+    #[arg(long = "meminfo-path", env = "EASYCHECK_MEMINFO_PATH")]
+    pub meminfo_path: Option<PathBuf>,
+    #[arg(long = "vmstat-path", env = "EASYCHECK_VMSTAT_PATH")]
+    pub vmstat_path: Option<PathBuf>,
+    #[arg(long = "memory-min-available", env = "EASYCHECK_MEMORY_MIN_AVAILABLE")]
+    pub memory_min_available: Option<Threshold>,
+    #[arg(
+        long = "memory-warn-available",
+        env = "EASYCHECK_MEMORY_WARN_AVAILABLE"
+    )]
+    pub memory_warn_available: Option<Threshold>,
+    #[arg(long = "swap-max-used", env = "EASYCHECK_SWAP_MAX_USED")]
+    pub swap_max_used: Option<Threshold>,
+    #[arg(long = "swap-warn-used", env = "EASYCHECK_SWAP_WARN_USED")]
+    pub swap_warn_used: Option<Threshold>,
+    #[arg(long = "swap-max-in-rate", env = "EASYCHECK_SWAP_MAX_IN_RATE")]
+    pub swap_max_in_rate: Option<f64>,
+    #[arg(long = "swap-warn-in-rate", env = "EASYCHECK_SWAP_WARN_IN_RATE")]
+    pub swap_warn_in_rate: Option<f64>,
+    // check options for pressure stall information and load average checks
+    #[arg(long = "pressure-dir", env = "EASYCHECK_PRESSURE_DIR")]
+    pub pressure_directory: Option<PathBuf>,
+    #[arg(long = "loadavg-path", env = "EASYCHECK_LOADAVG_PATH")]
+    pub loadavg_path: Option<PathBuf>,
+    #[arg(
+        long = "pressure-max",
+        env = "EASYCHECK_PRESSURE_MAX",
+        value_delimiter = ','
+    )]
+    pub pressure_max: Option<Vec<PressureThreshold>>,
+    #[arg(
+        long = "pressure-warn",
+        env = "EASYCHECK_PRESSURE_WARN",
+        value_delimiter = ','
+    )]
+    pub pressure_warn: Option<Vec<PressureThreshold>>,
+    #[arg(long = "load-max-per-cpu", env = "EASYCHECK_LOAD_MAX_PER_CPU")]
+    pub load_max_per_cpu: Option<f64>,
+    #[arg(long = "load-warn-per-cpu", env = "EASYCHECK_LOAD_WARN_PER_CPU")]
+    pub load_warn_per_cpu: Option<f64>,
+    // check options for process liveness checks
+    #[arg(long = "proc-path", env = "EASYCHECK_PROC_PATH")]
+    pub proc_path: Option<PathBuf>,
+    #[arg(long = "process-pidfile", env = "EASYCHECK_PROCESS_PIDFILE")]
+    pub process_pidfile_path: Option<PathBuf>,
+    #[arg(long = "process-name", env = "EASYCHECK_PROCESS_NAME")]
+    pub process_name: Option<String>,
+    #[arg(long = "process-cmdline", env = "EASYCHECK_PROCESS_CMDLINE")]
+    pub process_cmdline_pattern: Option<String>,
+    #[arg(long = "process-min-count", env = "EASYCHECK_PROCESS_MIN_COUNT")]
+    pub process_min_count: Option<usize>,
+    #[arg(long = "process-max-count", env = "EASYCHECK_PROCESS_MAX_COUNT")]
+    pub process_max_count: Option<usize>,
+    #[arg(
+        long = "process-fail-on-zombie",
+        env = "EASYCHECK_PROCESS_FAIL_ON_ZOMBIE"
+    )]
+    pub process_fail_on_zombie: Option<bool>,
+    #[arg(
+        long = "process-fail-on-uninterruptible",
+        env = "EASYCHECK_PROCESS_FAIL_ON_UNINTERRUPTIBLE"
+    )]
+    pub process_fail_on_uninterruptible: Option<bool>,
+    // check options for file freshness checks
+    #[arg(long = "heartbeat-file", env = "EASYCHECK_HEARTBEAT_FILE")]
+    pub heartbeat_file_pattern: Option<String>,
+    #[arg(long = "heartbeat-max-age", env = "EASYCHECK_HEARTBEAT_MAX_AGE")]
+    pub heartbeat_max_age_seconds: Option<u64>,
+    #[arg(
+        long = "heartbeat-fail-if-missing",
+        env = "EASYCHECK_HEARTBEAT_FAIL_IF_MISSING"
+    )]
+    pub heartbeat_fail_if_missing: Option<bool>,
+    // check options for file content checks
+    #[arg(long = "content-file", env = "EASYCHECK_CONTENT_FILE")]
+    pub content_file_path: Option<PathBuf>,
+    #[arg(long = "content-max-size", env = "EASYCHECK_CONTENT_MAX_SIZE")]
+    pub content_max_size: Option<u64>,
+    #[arg(long = "content-expected", env = "EASYCHECK_CONTENT_EXPECTED")]
+    pub content_expected: Option<String>,
+    #[arg(long = "content-pattern", env = "EASYCHECK_CONTENT_PATTERN")]
+    pub content_pattern: Option<String>,
+    #[arg(long = "content-json-pointer", env = "EASYCHECK_CONTENT_JSON_POINTER")]
+    pub content_json_pointer: Option<String>,
+    // check options for exec checks
+    #[arg(long = "exec-command", env = "EASYCHECK_EXEC_COMMAND")]
+    pub exec_command: Option<String>,
+    #[arg(
+        long = "exec-arg",
+        env = "EASYCHECK_EXEC_ARG",
+        allow_hyphen_values = true
+    )]
+    pub exec_args: Option<Vec<String>>,
+    #[arg(long = "exec-env", env = "EASYCHECK_EXEC_ENV")]
+    pub exec_env: Option<Vec<String>>,
+    #[arg(long = "exec-workdir", env = "EASYCHECK_EXEC_WORKDIR")]
+    pub exec_working_directory: Option<PathBuf>,
+    #[arg(long = "exec-timeout", env = "EASYCHECK_EXEC_TIMEOUT")]
+    pub exec_timeout_seconds: Option<u64>,
+    #[arg(long = "exec-user", env = "EASYCHECK_EXEC_USER")]
+    pub exec_user: Option<String>,
+    // check options for remote easycheck checks
+    #[arg(long = "remote-url", env = "EASYCHECK_REMOTE_URL")]
+    pub remote_check_url: Option<Uri>,
+    #[arg(long = "remote-name", env = "EASYCHECK_REMOTE_NAME")]
+    pub remote_check_name: Option<String>,
+    #[arg(long = "remote-timeout", env = "EASYCHECK_REMOTE_TIMEOUT")]
+    pub remote_check_timeout_seconds: Option<u64>,
 }
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use crate::status::check_settings::CheckSettings;
+use crate::status::status_checker::StatusChecker;
+
+/// Orders the given checks into waves based on their dependencies, identified by
+/// their index. All checks of a wave only depend on checks of earlier waves and can
+/// be executed in parallel. Fails if the dependencies of some checks form a cycle.
+pub(crate) fn execution_waves(
+    status_checker: &[Box<dyn StatusChecker>],
+    check_settings: &HashMap<String, CheckSettings>,
+) -> anyhow::Result<Vec<Vec<usize>>> {
+    let dependencies: Vec<Vec<usize>> = status_checker
+        .iter()
+        .map(|checker| {
+            let depends_on = check_settings
+                .get(checker.check_id())
+                .map(|settings| settings.depends_on.as_slice())
+                .unwrap_or_default();
+            status_checker
+                .iter()
+                .enumerate()
+                .filter(|(_, dependency)| {
+                    depends_on
+                        .iter()
+                        .any(|check_id| check_id == dependency.check_id())
+                })
+                .map(|(index, _)| index)
+                .collect()
+        })
+        .collect();
+
+    let mut scheduled = vec![false; status_checker.len()];
+    let mut waves: Vec<Vec<usize>> = vec![];
+    while scheduled.iter().any(|scheduled| !scheduled) {
+        let wave: Vec<usize> = (0..status_checker.len())
+            .filter(|&index| !scheduled[index])
+            .filter(|&index| {
+                dependencies[index]
+                    .iter()
+                    .all(|&dependency| scheduled[dependency])
+            })
+            .collect();
+        if wave.is_empty() {
+            let cyclic_check_ids: Vec<&str> = (0..status_checker.len())
+                .filter(|&index| !scheduled[index])
+                .map(|index| status_checker[index].check_id())
+                .collect();
+            bail!(
+                "dependencies of checks {} form a cycle",
+                cyclic_check_ids.join(", ")
+            );
+        }
+        for &index in &wave {
+            scheduled[index] = true;
+        }
+        waves.push(wave);
+    }
+
+    Ok(waves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+    use crate::status::status_checker::StatusCheckResult;
+
+    struct IdChecker(&'static str);
+    #[async_trait::async_trait]
+    impl StatusChecker for IdChecker {
+        fn from_options(_: &Options) -> anyhow::Result<Option<Self>> {
+            Ok(None)
+        }
+        fn check_name(&self) -> String {
+            self.0.to_string()
+        }
+        fn check_id(&self) -> &str {
+            self.0
+        }
+        async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+            Ok(StatusCheckResult::new_success())
+        }
+    }
+
+    fn checkers(check_ids: &[&'static str]) -> Vec<Box<dyn StatusChecker>> {
+        check_ids
+            .iter()
+            .map(|check_id| Box::new(IdChecker(check_id)) as Box<dyn StatusChecker>)
+            .collect()
+    }
+
+    fn depends_on(dependencies: &[(&str, &str)]) -> HashMap<String, CheckSettings> {
+        let mut check_settings: HashMap<String, CheckSettings> = HashMap::new();
+        for (check_id, dependency) in dependencies {
+            check_settings
+                .entry(check_id.to_string())
+                .or_default()
+                .depends_on
+                .push(dependency.to_string());
+        }
+        check_settings
+    }
+
+    #[test]
+    fn checks_without_dependencies_run_in_one_wave() {
+        let waves = execution_waves(&checkers(&["a", "b", "c"]), &HashMap::new()).unwrap();
+        assert_eq!(waves, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn dependents_run_after_their_dependencies() {
+        let check_settings = depends_on(&[("http", "socket"), ("socket", "memory")]);
+        let waves = execution_waves(
+            &checkers(&["http", "disk", "socket", "memory"]),
+            &check_settings,
+        )
+        .unwrap();
+        assert_eq!(waves, vec![vec![1, 3], vec![2], vec![0]]);
+    }
+
+    #[test]
+    fn cyclic_dependencies_are_rejected() {
+        let check_settings = depends_on(&[("a", "b"), ("b", "a")]);
+        let error = execution_waves(&checkers(&["a", "b", "c"]), &check_settings).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "dependencies of checks a, b form a cycle"
+        );
+
+        let check_settings = depends_on(&[("a", "a")]);
+        assert!(execution_waves(&checkers(&["a"]), &check_settings).is_err());
+    }
+}
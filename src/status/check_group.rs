@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use crate::options::Options;
+use crate::status::status_checker::{CheckSeverity, StatusChecker};
+use crate::status::status_holder::FailingCheck;
+
+/// The policy how many members of a check group must pass for the group to pass.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum GroupPolicy {
+    /// All members must pass.
+    All,
+    /// At least one member must pass.
+    Any,
+    /// At least the given amount of members must pass.
+    AtLeast(usize),
+    /// At least the given percentage of members must pass.
+    Percentage(u8),
+}
+
+impl FromStr for GroupPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "all" => Ok(GroupPolicy::All),
+            "any" => Ok(GroupPolicy::Any),
+            policy => match policy.strip_suffix('%') {
+                Some(percentage) => match percentage.trim().parse::<u8>() {
+                    Ok(percentage) if (1..=100).contains(&percentage) => {
+                        Ok(GroupPolicy::Percentage(percentage))
+                    }
+                    _ => Err(format!("invalid group percentage {}", value)),
+                },
+                None => match policy.parse::<usize>() {
+                    Ok(amount) if amount > 0 => Ok(GroupPolicy::AtLeast(amount)),
+                    _ => Err(format!("unknown group policy {}", value)),
+                },
+            },
+        }
+    }
+}
+
+impl Display for GroupPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GroupPolicy::All => write!(f, "all"),
+            GroupPolicy::Any => write!(f, "any"),
+            GroupPolicy::AtLeast(amount) => write!(f, "at least {}", amount),
+            GroupPolicy::Percentage(percentage) => write!(f, "at least {}%", percentage),
+        }
+    }
+}
+
+impl GroupPolicy {
+    /// Returns if this policy is met by the given amount of passing members.
+    fn is_met(&self, passing: usize, members: usize) -> bool {
+        match self {
+            GroupPolicy::All => passing == members,
+            GroupPolicy::Any => passing > 0,
+            GroupPolicy::AtLeast(amount) => passing >= *amount,
+            GroupPolicy::Percentage(percentage) => passing * 100 >= *percentage as usize * members,
+        }
+    }
+}
+
+/// A group of checks that is evaluated as a unit. As long as the policy of
+/// the group is met, failures of its members are reported as warnings only.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CheckGroup {
+    /// The name of the group.
+    pub name: String,
+    /// The ids of the checks in the group.
+    pub check_ids: Vec<String>,
+    /// The policy how many members must pass for the group to pass.
+    pub policy: GroupPolicy,
+}
+
+impl CheckGroup {
+    /// Collects the check groups from the given options. Fails if a group references a
+    /// check that is not enabled, or if a check is a member of multiple groups.
+    pub fn from_options(
+        options: &Options,
+        status_checker: &[Box<dyn StatusChecker>],
+    ) -> anyhow::Result<Vec<CheckGroup>> {
+        let enabled_check_ids: HashSet<&str> = status_checker
+            .iter()
+            .map(|checker| checker.check_id())
+            .collect();
+        let mut grouped_check_ids: HashSet<&str> = HashSet::new();
+        let mut check_groups: Vec<CheckGroup> = vec![];
+        for members in options.check_groups.iter().flatten() {
+            if check_groups
+                .iter()
+                .any(|group| group.name == members.check_id)
+            {
+                bail!("group {} is defined multiple times", members.check_id);
+            }
+            for check_id in &members.value.0 {
+                if !enabled_check_ids.contains(check_id.as_str()) {
+                    bail!(
+                        "cannot add check {} to group {}: check is not enabled",
+                        check_id,
+                        members.check_id
+                    );
+                }
+                if !grouped_check_ids.insert(check_id) {
+                    bail!("check {} is a member of multiple groups", check_id);
+                }
+            }
+            check_groups.push(CheckGroup {
+                name: members.check_id.clone(),
+                check_ids: members.value.0.clone(),
+                policy: GroupPolicy::All,
+            });
+        }
+
+        for policy in options.check_group_policy.iter().flatten() {
+            let Some(group) = check_groups
+                .iter_mut()
+                .find(|group| group.name == policy.check_id)
+            else {
+                bail!(
+                    "cannot configure group {}: group is not defined",
+                    policy.check_id
+                );
+            };
+            if let GroupPolicy::AtLeast(amount) = policy.value {
+                if amount > group.check_ids.len() {
+                    bail!(
+                        "group {} has only {} checks, but requires at least {}",
+                        group.name,
+                        group.check_ids.len(),
+                        amount
+                    );
+                }
+            }
+            group.policy = policy.value;
+        }
+
+        Ok(check_groups)
+    }
+
+    /// Evaluates this group based on the given failures of its members. Members
+    /// failing critically are considered as not passing. If the policy of the group
+    /// is met, the failures are reduced to warnings. The failure reasons of the
+    /// members are prefixed with the state of the group either way.
+    pub fn evaluate(&self, member_failures: Vec<&mut FailingCheck>) {
+        let members = self.check_ids.len();
+        let failed = member_failures
+            .iter()
+            .filter(|failure| failure.severity == CheckSeverity::Critical)
+            .count();
+        let passing = members.saturating_sub(failed);
+        let policy_met = self.policy.is_met(passing, members);
+        for failure in member_failures {
+            failure.failure_reason = format!(
+                "group {} has {} of {} checks passing, requires {}: {}",
+                self.name, passing, members, self.policy, failure.failure_reason
+            );
+            if policy_met {
+                failure.severity = failure.severity.min(CheckSeverity::Warning);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(severity: CheckSeverity) -> FailingCheck {
+        FailingCheck {
+            check_name: "test check".to_string(),
+            failure_reason: "down".to_string(),
+            severity,
+            perfdata: vec![],
+            streak: None,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
+        }
+    }
+
+    fn group(policy: GroupPolicy) -> CheckGroup {
+        CheckGroup {
+            name: "api".to_string(),
+            check_ids: vec!["http.1".into(), "http.2".into(), "http.3".into()],
+            policy,
+        }
+    }
+
+    #[test]
+    fn parses_group_policy() {
+        assert_eq!("all".parse::<GroupPolicy>(), Ok(GroupPolicy::All));
+        assert_eq!("Any".parse::<GroupPolicy>(), Ok(GroupPolicy::Any));
+        assert_eq!("2".parse::<GroupPolicy>(), Ok(GroupPolicy::AtLeast(2)));
+        assert_eq!(
+            "50%".parse::<GroupPolicy>(),
+            Ok(GroupPolicy::Percentage(50))
+        );
+        assert!("0".parse::<GroupPolicy>().is_err());
+        assert!("0%".parse::<GroupPolicy>().is_err());
+        assert!("101%".parse::<GroupPolicy>().is_err());
+        assert!("most".parse::<GroupPolicy>().is_err());
+    }
+
+    #[test]
+    fn policy_is_met_by_passing_members() {
+        assert!(GroupPolicy::All.is_met(3, 3));
+        assert!(!GroupPolicy::All.is_met(2, 3));
+        assert!(GroupPolicy::Any.is_met(1, 3));
+        assert!(!GroupPolicy::Any.is_met(0, 3));
+        assert!(GroupPolicy::AtLeast(2).is_met(2, 3));
+        assert!(!GroupPolicy::AtLeast(2).is_met(1, 3));
+        assert!(GroupPolicy::Percentage(50).is_met(2, 4));
+        assert!(!GroupPolicy::Percentage(50).is_met(1, 3));
+    }
+
+    #[test]
+    fn met_policy_reduces_failures_to_warnings() {
+        let mut down = failure(CheckSeverity::Critical);
+        group(GroupPolicy::AtLeast(2)).evaluate(vec![&mut down]);
+        assert_eq!(down.severity, CheckSeverity::Warning);
+        assert_eq!(
+            down.failure_reason,
+            "group api has 2 of 3 checks passing, requires at least 2: down"
+        );
+    }
+
+    #[test]
+    fn missed_policy_keeps_failures_critical() {
+        let mut first = failure(CheckSeverity::Critical);
+        let mut second = failure(CheckSeverity::Critical);
+        group(GroupPolicy::AtLeast(2)).evaluate(vec![&mut first, &mut second]);
+        assert_eq!(first.severity, CheckSeverity::Critical);
+        assert_eq!(second.severity, CheckSeverity::Critical);
+        assert!(first.failure_reason.starts_with("group api has 1 of 3"));
+    }
+
+    #[test]
+    fn warnings_count_as_passing() {
+        let mut warning = failure(CheckSeverity::Warning);
+        group(GroupPolicy::All).evaluate(vec![&mut warning]);
+        assert_eq!(warning.severity, CheckSeverity::Warning);
+        assert!(warning.failure_reason.contains("3 of 3 checks passing"));
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use crate::options::Options;
+use crate::status::status_checker::{CheckSeverity, StatusChecker};
+
+/// The value of a setting for a single check, parsed from `<check id>=<value>`,
+/// for example `disk=warning` or `http=3`. Also used for settings of check
+/// groups and endpoints, which are referenced by their name or path instead.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CheckSetting<T> {
+    pub check_id: String,
+    pub value: T,
+}
+
+impl<T> FromStr for CheckSetting<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (check_id, setting) = value
+            .split_once('=')
+            .ok_or_else(|| format!("{} must have the format <check>=<value>", value))?;
+        let setting = setting
+            .trim()
+            .parse()
+            .map_err(|error| format!("invalid value for check {}: {}", check_id, error))?;
+        Ok(Self {
+            check_id: check_id.trim().to_string(),
+            value: setting,
+        })
+    }
+}
+
+/// A list of check ids, parsed from `<check id>+<check id>...`,
+/// for example `http.1+http.2+http.3`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CheckIds(pub Vec<String>);
+
+impl FromStr for CheckIds {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let check_ids: Vec<String> = value
+            .split('+')
+            .map(|check_id| check_id.trim().to_string())
+            .filter(|check_id| !check_id.is_empty())
+            .collect();
+        if check_ids.is_empty() {
+            return Err(String::from("no checks given"));
+        }
+        Ok(Self(check_ids))
+    }
+}
+
+/// The state of a check that is assumed until the check produced its first
+/// result, or during the startup grace period until the check succeeded once.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum CheckInitialState {
+    /// The check is assumed as passing.
+    Up,
+    /// The check is assumed as failing.
+    Down,
+    /// The check is reported as pending, which marks the service as down.
+    Pending,
+}
+
+impl FromStr for CheckInitialState {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "up" => Ok(CheckInitialState::Up),
+            "down" => Ok(CheckInitialState::Down),
+            "pending" => Ok(CheckInitialState::Pending),
+            _ => Err(format!("unknown initial state {}", value)),
+        }
+    }
+}
+
+/// The settings how the results of a single check are evaluated.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CheckSettings {
+    /// The maximum severity failures of the check are reported with.
+    pub max_severity: CheckSeverity,
+    /// The amount of consecutive successful results after which
+    /// a failing check is considered as successful again.
+    pub rise: u32,
+    /// The amount of consecutive failed results after which a
+    /// successful check is considered as failing.
+    pub fall: u32,
+    /// The state of the check until it produced its first result.
+    pub initial_state: CheckInitialState,
+    /// The ids of the checks this check depends on. The check is
+    /// skipped if one of its dependencies failed critically.
+    pub depends_on: Vec<String>,
+    /// If failures of the check drain the service instead of marking it as down.
+    pub drain: bool,
+}
+
+impl Default for CheckSettings {
+    fn default() -> Self {
+        Self {
+            max_severity: CheckSeverity::Critical,
+            rise: 1,
+            fall: 1,
+            initial_state: CheckInitialState::Pending,
+            depends_on: vec![],
+            drain: false,
+        }
+    }
+}
+
+impl CheckSettings {
+    /// Collects the settings of all given checks from the given options, keyed by
+    /// check id. Fails if a setting references a check that is not enabled.
+    pub fn from_options(
+        options: &Options,
+        status_checker: &[Box<dyn StatusChecker>],
+    ) -> anyhow::Result<HashMap<String, CheckSettings>> {
+        let mut check_settings: HashMap<String, CheckSettings> = status_checker
+            .iter()
+            .map(|checker| (checker.check_id().to_string(), CheckSettings::default()))
+            .collect();
+
+        for severity in options.check_severity.iter().flatten() {
+            if severity.value == CheckSeverity::Ok {
+                bail!(
+                    "severity of check {} must be warning or critical",
+                    severity.check_id
+                );
+            }
+            Self::lookup(&mut check_settings, &severity.check_id)?.max_severity = severity.value;
+        }
+        for rise in options.check_rise.iter().flatten() {
+            Self::lookup(&mut check_settings, &rise.check_id)?.rise = rise.value.get();
+        }
+        for fall in options.check_fall.iter().flatten() {
+            Self::lookup(&mut check_settings, &fall.check_id)?.fall = fall.value.get();
+        }
+        for initial_state in options.check_initial_state.iter().flatten() {
+            Self::lookup(&mut check_settings, &initial_state.check_id)?.initial_state =
+                initial_state.value;
+        }
+        for dependency in options.check_depends_on.iter().flatten() {
+            if !check_settings.contains_key(&dependency.value) {
+                bail!(
+                    "check {} cannot depend on check {}: check is not enabled",
+                    dependency.check_id,
+                    dependency.value
+                );
+            }
+            Self::lookup(&mut check_settings, &dependency.check_id)?
+                .depends_on
+                .push(dependency.value.clone());
+        }
+        for check_id in options.check_drain.iter().flatten() {
+            Self::lookup(&mut check_settings, check_id.trim())?.drain = true;
+        }
+
+        Ok(check_settings)
+    }
+
+    fn lookup<'a>(
+        check_settings: &'a mut HashMap<String, CheckSettings>,
+        check_id: &str,
+    ) -> anyhow::Result<&'a mut CheckSettings> {
+        match check_settings.get_mut(check_id) {
+            Some(settings) => Ok(settings),
+            None => bail!("cannot configure check {}: check is not enabled", check_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn parses_check_setting() {
+        let setting: CheckSetting<CheckSeverity> = "disk=Warning".parse().unwrap();
+        assert_eq!(setting.check_id, "disk");
+        assert_eq!(setting.value, CheckSeverity::Warning);
+
+        let setting: CheckSetting<NonZeroU32> = " http = 3".parse().unwrap();
+        assert_eq!(setting.check_id, "http");
+        assert_eq!(setting.value.get(), 3);
+
+        let setting: CheckSetting<CheckInitialState> = "http=up".parse().unwrap();
+        assert_eq!(setting.value, CheckInitialState::Up);
+
+        let setting: CheckSetting<CheckIds> = "api = http.1+ http.2 ".parse().unwrap();
+        assert_eq!(setting.check_id, "api");
+        assert_eq!(
+            setting.value,
+            CheckIds(vec!["http.1".into(), "http.2".into()])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_check_setting() {
+        assert!("disk".parse::<CheckSetting<CheckSeverity>>().is_err());
+        assert!("disk=fatal".parse::<CheckSetting<CheckSeverity>>().is_err());
+        assert!("http=0".parse::<CheckSetting<NonZeroU32>>().is_err());
+        assert!("http=starting"
+            .parse::<CheckSetting<CheckInitialState>>()
+            .is_err());
+        assert!("api=".parse::<CheckSetting<CheckIds>>().is_err());
+    }
+}
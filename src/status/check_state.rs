@@ -0,0 +1,171 @@
+use crate::status::check_settings::CheckSettings;
+use crate::status::status_holder::{CheckStreak, FailingCheck};
+
+/// The state of a single check across multiple check cycles, used to
+/// only change the reported status after consecutive equal results.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CheckState {
+    /// If the check is currently reported as failing, None until
+    /// the first result of the check was recorded.
+    failing: Option<bool>,
+    /// The consecutive results of the check, either successes or failures.
+    streak: CheckStreak,
+    /// The most recent failure of the check, reported while
+    /// the check did not yet rise after failing.
+    last_failure: Option<FailingCheck>,
+}
+
+impl CheckState {
+    /// Returns if a result of the check was recorded already.
+    pub fn has_result(&self) -> bool {
+        self.failing.is_some()
+    }
+
+    /// Returns the consecutive results of the check, None until
+    /// the first result of the check was recorded.
+    pub fn streak(&self) -> Option<CheckStreak> {
+        self.failing.map(|_| self.streak)
+    }
+
+    /// Records the given result of the check. The first recorded result is used as is,
+    /// afterward the reported status only changes after `fall` consecutive failures or
+    /// `rise` consecutive successes. Returns the failing check to report, if the check
+    /// is considered as failing.
+    pub fn record(
+        &mut self,
+        failure: Option<FailingCheck>,
+        settings: &CheckSettings,
+    ) -> Option<FailingCheck> {
+        match failure {
+            Some(failure) => {
+                self.streak.failures += 1;
+                self.streak.successes = 0;
+                self.last_failure = Some(failure);
+                if self.failing.is_none() || self.streak.failures >= settings.fall {
+                    self.failing = Some(true);
+                }
+            }
+            None => {
+                self.streak.successes += 1;
+                self.streak.failures = 0;
+                if self.failing.is_none() || self.streak.successes >= settings.rise {
+                    self.failing = Some(false);
+                    self.last_failure = None;
+                }
+            }
+        }
+
+        match self.failing {
+            Some(true) => self
+                .last_failure
+                .clone()
+                .map(|failure| failure.with_streak(self.streak)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::status_checker::CheckSeverity;
+
+    fn settings(rise: u32, fall: u32) -> CheckSettings {
+        CheckSettings {
+            rise,
+            fall,
+            ..CheckSettings::default()
+        }
+    }
+
+    fn failure(reason: &str) -> Option<FailingCheck> {
+        Some(FailingCheck {
+            check_name: "test check".to_string(),
+            failure_reason: reason.to_string(),
+            severity: CheckSeverity::Critical,
+            perfdata: vec![],
+            streak: None,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
+        })
+    }
+
+    #[test]
+    fn first_result_is_used_as_is() {
+        let settings = settings(3, 3);
+        let mut state = CheckState::default();
+        let reported = state.record(failure("down"), &settings).unwrap();
+        assert_eq!(reported.failure_reason, "down");
+        assert_eq!(
+            reported.streak,
+            Some(CheckStreak {
+                failures: 1,
+                successes: 0
+            })
+        );
+
+        let mut state = CheckState::default();
+        assert!(!state.has_result());
+        assert!(state.record(None, &settings).is_none());
+        assert!(state.has_result());
+    }
+
+    #[test]
+    fn fails_after_fall_consecutive_failures() {
+        let settings = settings(1, 3);
+        let mut state = CheckState::default();
+        assert!(state.record(None, &settings).is_none());
+        assert!(state.record(failure("a"), &settings).is_none());
+        assert!(state.record(failure("b"), &settings).is_none());
+        let reported = state.record(failure("c"), &settings).unwrap();
+        assert_eq!(reported.failure_reason, "c");
+        assert_eq!(reported.streak.unwrap().failures, 3);
+    }
+
+    #[test]
+    fn streak_is_kept_while_failures_are_dampened() {
+        let settings = settings(1, 3);
+        let mut state = CheckState::default();
+        assert_eq!(state.streak(), None);
+        assert!(state.record(None, &settings).is_none());
+        assert!(state.record(failure("a"), &settings).is_none());
+        assert!(state.record(failure("b"), &settings).is_none());
+        assert_eq!(
+            state.streak(),
+            Some(CheckStreak {
+                failures: 2,
+                successes: 0
+            })
+        );
+    }
+
+    #[test]
+    fn success_resets_failure_streak() {
+        let settings = settings(1, 2);
+        let mut state = CheckState::default();
+        assert!(state.record(None, &settings).is_none());
+        assert!(state.record(failure("a"), &settings).is_none());
+        assert!(state.record(None, &settings).is_none());
+        assert!(state.record(failure("b"), &settings).is_none());
+        assert!(state.record(failure("c"), &settings).is_some());
+    }
+
+    #[test]
+    fn rises_after_rise_consecutive_successes() {
+        let settings = settings(2, 1);
+        let mut state = CheckState::default();
+        assert!(state.record(failure("down"), &settings).is_some());
+
+        let reported = state.record(None, &settings).unwrap();
+        assert_eq!(reported.failure_reason, "down");
+        assert_eq!(
+            reported.streak,
+            Some(CheckStreak {
+                failures: 0,
+                successes: 1
+            })
+        );
+        assert!(state.record(None, &settings).is_none());
+    }
+}
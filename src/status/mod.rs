@@ -1,3 +1,9 @@
+pub(crate) mod check_dependencies;
+pub(crate) mod check_group;
+pub(crate) mod check_settings;
+pub(crate) mod check_state;
 pub mod status_checker;
+pub(crate) mod status_endpoint;
 pub(crate) mod status_holder;
 pub(crate) mod status_manager;
+pub(crate) mod status_watchdog;
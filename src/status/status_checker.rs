@@ -1,6 +1,11 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 
 use crate::options::Options;
+use crate::util::file_watcher::WatchedPath;
 
 /// Defines the shared behavior how status checks are executed.
 #[async_trait]
@@ -10,9 +15,29 @@ pub trait StatusChecker: Send + Sync {
     where
         Self: Sized;
 
+    /// Constructs all instances of this checker based on the given options. Checks
+    /// that can only be configured once construct at most one instance.
+    fn all_from_options(options: &Options) -> anyhow::Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_options(options)?.into_iter().collect())
+    }
+
     /// Get a descriptive name of this check.
     fn check_name(&self) -> String;
 
+    /// Get a short identifier of this check, used to reference the check
+    /// in options (for example `disk` or `http`). Checks that can be configured
+    /// multiple times are suffixed with their index (for example `http.2`).
+    fn check_id(&self) -> &str;
+
+    /// Get the paths whose changes should immediately trigger a check cycle,
+    /// instead of waiting for the next revalidation.
+    fn watched_paths(&self) -> Vec<WatchedPath> {
+        vec![]
+    }
+
     /// Called when the status check should be executed. When the status
     /// checking fails (returns Err) the check is considered as failed,
     /// but all other checks will still be executed. Only if a successful
@@ -21,14 +46,96 @@ pub trait StatusChecker: Send + Sync {
     async fn execute_check(&self) -> anyhow::Result<StatusCheckResult>;
 }
 
+/// The severity of a status check result. Only critical results
+/// mark the service as down, warnings are reported but keep it up.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckSeverity {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl Display for CheckSeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckSeverity::Ok => write!(f, "ok"),
+            CheckSeverity::Warning => write!(f, "warning"),
+            CheckSeverity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl FromStr for CheckSeverity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "ok" => Ok(CheckSeverity::Ok),
+            "warning" => Ok(CheckSeverity::Warning),
+            "critical" => Ok(CheckSeverity::Critical),
+            _ => Err(format!("unknown severity {}", value)),
+        }
+    }
+}
+
+/// A single measured value reported by a check, modeled after the
+/// performance data of nagios plugins.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PerformanceData {
+    /// The name of the measured value.
+    pub label: String,
+    /// The measured value.
+    pub value: f64,
+    /// The unit of measurement of the value, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// The warning range of the value, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// The critical range of the value, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical: Option<String>,
+    /// The minimum possible value, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// The maximum possible value, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+/// A single maintenance hold reported by a check, listed as its own failing
+/// check while the check fails.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MaintenanceHold {
+    /// The name the hold is listed with.
+    pub name: String,
+    /// A descriptive reason of the hold.
+    pub reason: String,
+}
+
 /// The result of a status check.
 pub(crate) struct StatusCheckResult {
     /// The reason why the status check failed. If present the check is
     /// considered as failed, if absent the check was successful.
     pub failure_reason: Option<String>,
+    /// The severity of the result, ok for successful results.
+    pub severity: CheckSeverity,
     /// Indicate if results from other status checkers should be ignored
     /// and only this result should be returned.
     pub ignore_other_results: bool,
+    /// Indicate if a failure of this result is a maintenance hold, which
+    /// reports the service as in maintenance instead of down.
+    pub maintenance: bool,
+    /// Indicate if a failure of this result drains the service, which reports
+    /// the service as draining instead of down.
+    pub drain: bool,
+    /// The individual maintenance holds of a failed result, which are listed
+    /// instead of the result itself if present.
+    pub holds: Vec<MaintenanceHold>,
+    /// Additional measured values of the check that are reported
+    /// alongside the failure reason.
+    pub perfdata: Vec<PerformanceData>,
 }
 
 impl StatusCheckResult {
@@ -36,7 +143,12 @@ impl StatusCheckResult {
     pub fn new_success() -> Self {
         Self {
             failure_reason: None,
+            severity: CheckSeverity::Ok,
             ignore_other_results: false,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
+            perfdata: vec![],
         }
     }
 
@@ -44,7 +156,31 @@ impl StatusCheckResult {
     pub fn new_failure(failure_reason: String) -> Self {
         Self {
             failure_reason: Some(failure_reason),
+            severity: CheckSeverity::Critical,
             ignore_other_results: false,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
+            perfdata: vec![],
+        }
+    }
+
+    /// Creates a new status check result with a warning using the provided reason.
+    /// Warnings are reported, but do not mark the service as down.
+    pub fn new_warning(failure_reason: String) -> Self {
+        Self {
+            severity: CheckSeverity::Warning,
+            ..Self::new_failure(failure_reason)
+        }
+    }
+
+    /// Creates a new status check result with the given severity using the provided
+    /// reason. The reason is dropped for the ok severity.
+    pub fn new_with_severity(severity: CheckSeverity, failure_reason: String) -> Self {
+        match severity {
+            CheckSeverity::Ok => Self::new_success(),
+            CheckSeverity::Warning => Self::new_warning(failure_reason),
+            CheckSeverity::Critical => Self::new_failure(failure_reason),
         }
     }
 
@@ -53,10 +189,36 @@ impl StatusCheckResult {
     /// this flag set will be used as the final response.
     pub fn ignore_other_results(self) -> Self {
         Self {
-            failure_reason: self.failure_reason,
             ignore_other_results: true,
+            ..self
+        }
+    }
+
+    /// Marks a failure of this result as maintenance hold.
+    pub fn maintenance_hold(self) -> Self {
+        Self {
+            maintenance: true,
+            ..self
         }
     }
+
+    /// Marks a failure of this result as drain hold.
+    pub fn drain_hold(self) -> Self {
+        Self {
+            drain: true,
+            ..self
+        }
+    }
+
+    /// Attaches the given individual maintenance holds to this result.
+    pub fn with_holds(self, holds: Vec<MaintenanceHold>) -> Self {
+        Self { holds, ..self }
+    }
+
+    /// Attaches the given performance data to this result.
+    pub fn with_perfdata(self, perfdata: Vec<PerformanceData>) -> Self {
+        Self { perfdata, ..self }
+    }
 }
 
 #[cfg(test)]
@@ -74,17 +236,32 @@ mod tests {
     fn new_failure_has_failure_reason_and_no_ignore() {
         let result = StatusCheckResult::new_failure("something broke".to_string());
         assert_eq!(result.failure_reason.as_deref(), Some("something broke"));
+        assert_eq!(result.severity, CheckSeverity::Critical);
         assert!(!result.ignore_other_results);
     }
 
     #[test]
-    fn ignore_other_results_sets_flag() {
-        let result = StatusCheckResult::new_success().ignore_other_results();
-        assert!(result.ignore_other_results);
+    fn new_warning_has_failure_reason_and_warning_severity() {
+        let result = StatusCheckResult::new_warning("disk almost full".to_string());
+        assert_eq!(result.failure_reason.as_deref(), Some("disk almost full"));
+        assert_eq!(result.severity, CheckSeverity::Warning);
+    }
+
+    #[test]
+    fn new_with_severity_uses_severity() {
+        let result = StatusCheckResult::new_with_severity(CheckSeverity::Warning, "a".to_string());
+        assert_eq!(result.failure_reason.as_deref(), Some("a"));
+        assert_eq!(result.severity, CheckSeverity::Warning);
+
+        let result = StatusCheckResult::new_with_severity(CheckSeverity::Ok, "a".to_string());
         assert!(result.failure_reason.is_none());
+        assert_eq!(result.severity, CheckSeverity::Ok);
+    }
 
-        let result = StatusCheckResult::new_failure("fail".to_string()).ignore_other_results();
-        assert!(result.ignore_other_results);
-        assert_eq!(result.failure_reason.as_deref(), Some("fail"));
+    #[test]
+    fn parses_severity() {
+        assert_eq!("Warning".parse(), Ok(CheckSeverity::Warning));
+        assert_eq!("critical".parse(), Ok(CheckSeverity::Critical));
+        assert!("fatal".parse::<CheckSeverity>().is_err());
     }
 }
@@ -0,0 +1,341 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use axum::http::StatusCode;
+
+use crate::admin_api::ADMIN_PATH_PREFIX;
+use crate::options::Options;
+use crate::status::status_checker::StatusChecker;
+use crate::status::status_holder::ServiceState;
+use crate::status_response::StatusResponseSettings;
+
+/// The path of the endpoint serving the status of all checks by default.
+const ROOT_ENDPOINT_PATH: &str = "/";
+
+/// An http endpoint serving the status derived from a subset of the checks.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct StatusEndpoint {
+    /// The path the endpoint is served on.
+    pub path: String,
+    /// The ids of the checks the status is derived from.
+    pub check_ids: Vec<String>,
+    /// The status codes of the endpoint, overriding the globally configured ones.
+    pub response_settings: EndpointResponseSettings,
+}
+
+/// The status codes of a single endpoint per state of the service. States without
+/// status code are answered with the globally configured status code.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct EndpointResponseSettings {
+    pub up_status_code: Option<StatusCode>,
+    pub degraded_status_code: Option<StatusCode>,
+    pub maintenance_status_code: Option<StatusCode>,
+    pub drain_status_code: Option<StatusCode>,
+    pub down_status_code: Option<StatusCode>,
+}
+
+impl EndpointResponseSettings {
+    /// Returns the status code to send for the given state of the service,
+    /// falling back to the given global response settings.
+    pub fn status_code(
+        &self,
+        state: ServiceState,
+        global_settings: &StatusResponseSettings,
+    ) -> StatusCode {
+        self.status_code_override(state)
+            .unwrap_or_else(|| global_settings.status_code(state))
+    }
+
+    fn status_code_override(&self, state: ServiceState) -> Option<StatusCode> {
+        match state {
+            ServiceState::Up => self.up_status_code,
+            ServiceState::Degraded => self.degraded_status_code,
+            ServiceState::Drain => self.drain_status_code,
+            ServiceState::Maintenance => self.maintenance_status_code,
+            ServiceState::Down => self.down_status_code,
+        }
+    }
+
+    fn set_status_code(&mut self, state: ServiceState, status_code: StatusCode) {
+        let status_code_override = match state {
+            ServiceState::Up => &mut self.up_status_code,
+            ServiceState::Degraded => &mut self.degraded_status_code,
+            ServiceState::Drain => &mut self.drain_status_code,
+            ServiceState::Maintenance => &mut self.maintenance_status_code,
+            ServiceState::Down => &mut self.down_status_code,
+        };
+        *status_code_override = Some(status_code);
+    }
+}
+
+impl StatusEndpoint {
+    /// Collects the endpoints from the given options. The root endpoint serves the
+    /// status of all checks unless configured otherwise. Fails if an endpoint
+    /// references a check that is not enabled.
+    pub fn from_options(
+        options: &Options,
+        status_checker: &[Box<dyn StatusChecker>],
+    ) -> anyhow::Result<Vec<StatusEndpoint>> {
+        let enabled_check_ids: HashSet<&str> = status_checker
+            .iter()
+            .map(|checker| checker.check_id())
+            .collect();
+        let mut endpoints = vec![StatusEndpoint {
+            path: String::from(ROOT_ENDPOINT_PATH),
+            check_ids: status_checker
+                .iter()
+                .map(|checker| checker.check_id().to_string())
+                .collect(),
+            response_settings: EndpointResponseSettings::default(),
+        }];
+        let mut root_endpoint_configured = false;
+        for endpoint_checks in options.status_endpoints.iter().flatten() {
+            let path = &endpoint_checks.check_id;
+            if !path.starts_with('/') {
+                bail!("endpoint path {} must start with a /", path);
+            }
+            // the router would interpret these characters as captures or wildcards
+            if path.contains(['{', '}', '*']) {
+                bail!("endpoint path {} must not contain {{, }} or *", path);
+            }
+            if path.starts_with(ADMIN_PATH_PREFIX) {
+                bail!("endpoint path {} is reserved for the admin api", path);
+            }
+            for check_id in &endpoint_checks.value.0 {
+                if !enabled_check_ids.contains(check_id.as_str()) {
+                    bail!(
+                        "cannot add check {} to endpoint {}: check is not enabled",
+                        check_id,
+                        path
+                    );
+                }
+            }
+
+            let check_ids = endpoint_checks.value.0.clone();
+            match endpoints.iter_mut().find(|endpoint| &endpoint.path == path) {
+                // the checks of the root endpoint can be configured once
+                Some(endpoint) if path == ROOT_ENDPOINT_PATH && !root_endpoint_configured => {
+                    endpoint.check_ids = check_ids;
+                    root_endpoint_configured = true;
+                }
+                Some(_) => bail!("endpoint {} is defined multiple times", path),
+                None => endpoints.push(StatusEndpoint {
+                    path: path.clone(),
+                    check_ids,
+                    response_settings: EndpointResponseSettings::default(),
+                }),
+            }
+        }
+
+        for status_code in options.endpoint_status_codes.iter().flatten() {
+            let Some((path, state)) = status_code.check_id.rsplit_once(':') else {
+                bail!(
+                    "endpoint status code {} must have the format <path>:<state>=<code>",
+                    status_code.check_id
+                );
+            };
+            let state = ServiceState::from_str(state)
+                .map_err(|error| anyhow!("cannot configure endpoint {}: {}", path, error))?;
+            let Some(endpoint) = endpoints.iter_mut().find(|endpoint| endpoint.path == path) else {
+                bail!(
+                    "cannot configure endpoint {}: endpoint is not defined",
+                    path
+                );
+            };
+            endpoint
+                .response_settings
+                .set_status_code(state, status_code.value);
+        }
+
+        Ok(endpoints)
+    }
+
+    /// Selects the checks of this endpoint to derive the status from, based on the
+    /// given comma separated check ids to include and exclude. All checks are included
+    /// if no checks to include are given. Fails if a given check is not part of this endpoint.
+    pub fn select_checks(
+        &self,
+        include: Option<&str>,
+        exclude: Option<&str>,
+    ) -> Result<Vec<String>, String> {
+        let include = self.parse_check_ids(include)?;
+        let exclude = self.parse_check_ids(exclude)?.unwrap_or_default();
+        Ok(self
+            .check_ids
+            .iter()
+            .filter(|check_id| {
+                include
+                    .as_ref()
+                    .map_or(true, |include| include.contains(check_id))
+            })
+            .filter(|check_id| !exclude.contains(check_id))
+            .cloned()
+            .collect())
+    }
+
+    fn parse_check_ids(&self, check_ids: Option<&str>) -> Result<Option<Vec<String>>, String> {
+        let Some(check_ids) = check_ids else {
+            return Ok(None);
+        };
+        let check_ids: Vec<String> = check_ids
+            .split(',')
+            .map(|check_id| check_id.trim().to_string())
+            .filter(|check_id| !check_id.is_empty())
+            .collect();
+        match check_ids
+            .iter()
+            .find(|check_id| !self.check_ids.contains(check_id))
+        {
+            Some(check_id) => Err(format!(
+                "check {} is not part of endpoint {}",
+                check_id, self.path
+            )),
+            None => Ok(Some(check_ids)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::status_checker::StatusCheckResult;
+    use clap::Parser;
+
+    struct IdChecker(&'static str);
+    #[async_trait::async_trait]
+    impl StatusChecker for IdChecker {
+        fn from_options(_: &Options) -> anyhow::Result<Option<Self>> {
+            Ok(None)
+        }
+        fn check_name(&self) -> String {
+            self.0.to_string()
+        }
+        fn check_id(&self) -> &str {
+            self.0
+        }
+        async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+            Ok(StatusCheckResult::new_success())
+        }
+    }
+
+    fn checkers() -> Vec<Box<dyn StatusChecker>> {
+        vec![Box::new(IdChecker("mtc")), Box::new(IdChecker("http"))]
+    }
+
+    fn options(args: &[&str]) -> Options {
+        let mut all_args = vec!["easycheck", "--bind", "127.0.0.1:0"];
+        all_args.extend_from_slice(args);
+        Options::try_parse_from(all_args).unwrap()
+    }
+
+    #[test]
+    fn root_endpoint_serves_all_checks_by_default() {
+        let endpoints = StatusEndpoint::from_options(&options(&[]), &checkers()).unwrap();
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints[0].path, "/");
+        assert_eq!(endpoints[0].check_ids, vec!["mtc", "http"]);
+        assert_eq!(
+            endpoints[0].response_settings,
+            EndpointResponseSettings::default()
+        );
+    }
+
+    #[test]
+    fn configures_endpoints_with_own_checks_and_status_code() {
+        let options = options(&[
+            "--endpoint",
+            "/live=http,/ready=mtc+http,/=mtc",
+            "--endpoint-status-code",
+            "/live:down=500,/live:maintenance=200",
+        ]);
+        let endpoints = StatusEndpoint::from_options(&options, &checkers()).unwrap();
+        assert_eq!(endpoints.len(), 3);
+        assert_eq!(endpoints[0].check_ids, vec!["mtc"]);
+        assert_eq!(endpoints[1].path, "/live");
+        assert_eq!(endpoints[1].check_ids, vec!["http"]);
+        assert_eq!(
+            endpoints[1].response_settings,
+            EndpointResponseSettings {
+                down_status_code: Some(StatusCode::INTERNAL_SERVER_ERROR),
+                maintenance_status_code: Some(StatusCode::OK),
+                ..EndpointResponseSettings::default()
+            }
+        );
+        assert_eq!(endpoints[2].path, "/ready");
+        assert_eq!(
+            endpoints[2].response_settings,
+            EndpointResponseSettings::default()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_endpoints() {
+        let invalid_args: [&[&str]; 6] = [
+            &["--endpoint", "live=http"],
+            &["--endpoint", "/live=disk"],
+            &["--endpoint", "/live=http,/live=mtc"],
+            &["--endpoint-status-code", "/live:down=500"],
+            &["--endpoint-status-code", "/=500"],
+            &["--endpoint-status-code", "/:offline=500"],
+        ];
+        for args in invalid_args {
+            assert!(
+                StatusEndpoint::from_options(&options(args), &checkers()).is_err(),
+                "{:?}",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_paths_the_router_cannot_serve() {
+        let rejected_paths = [
+            ("/*x", "endpoint path /*x must not contain {, } or *"),
+            ("/{id}", "endpoint path /{id} must not contain {, } or *"),
+            ("/live}", "endpoint path /live} must not contain {, } or *"),
+            (
+                "/admin/maintenance",
+                "endpoint path /admin/maintenance is reserved for the admin api",
+            ),
+        ];
+        for (path, expected_error) in rejected_paths {
+            let endpoint = format!("{}=mtc", path);
+            let error =
+                StatusEndpoint::from_options(&options(&["--endpoint", &endpoint]), &checkers())
+                    .unwrap_err();
+            assert_eq!(error.to_string(), expected_error);
+        }
+    }
+
+    #[test]
+    fn selects_included_and_not_excluded_checks() {
+        let endpoint = StatusEndpoint {
+            path: "/".to_string(),
+            check_ids: vec!["mtc".into(), "http".into(), "disk".into()],
+            response_settings: EndpointResponseSettings::default(),
+        };
+        assert_eq!(
+            endpoint.select_checks(None, None).unwrap(),
+            vec!["mtc", "http", "disk"]
+        );
+        assert_eq!(
+            endpoint.select_checks(Some("disk, http"), None).unwrap(),
+            vec!["http", "disk"]
+        );
+        assert_eq!(
+            endpoint.select_checks(None, Some("http")).unwrap(),
+            vec!["mtc", "disk"]
+        );
+        assert_eq!(
+            endpoint
+                .select_checks(Some("http,disk"), Some("disk"))
+                .unwrap(),
+            vec!["http"]
+        );
+        assert_eq!(
+            endpoint.select_checks(Some("memory"), None).unwrap_err(),
+            "check memory is not part of endpoint /"
+        );
+    }
+}
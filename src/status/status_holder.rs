@@ -1,17 +1,24 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use axum::http::StatusCode;
 use serde::Serialize;
 use tokio::sync::RwLock;
 use tokio::time::Instant;
 
-use crate::status::status_checker::StatusChecker;
+use crate::status::status_checker::{
+    CheckSeverity, MaintenanceHold, PerformanceData, StatusChecker,
+};
 
 /// Holder of the current status check result.
 #[derive(Clone, Debug)]
 pub(crate) struct StatusHolder {
-    /// The current status check result.
-    current_status: Arc<RwLock<StatusCheckResults>>,
+    /// The results of the checks in the current check cycle.
+    current_results: Arc<RwLock<CheckCycleResults>>,
+    /// The maximum age of the status check result, after which the
+    /// result is considered stale and the service as down.
+    max_result_age: Option<Duration>,
 }
 
 #[derive(Clone, Debug, Serialize, serde::Deserialize)]
@@ -20,29 +27,153 @@ pub(crate) struct FailingCheck {
     pub check_name: String,
     /// A descriptive reason why the check failed.
     pub failure_reason: String,
+    /// The severity of the failure. Failures reported without a
+    /// severity (by older instances) are considered critical.
+    #[serde(default = "default_failure_severity")]
+    pub severity: CheckSeverity,
+    /// The measured values reported by the check, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub perfdata: Vec<PerformanceData>,
+    /// The current consecutive results of the check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streak: Option<CheckStreak>,
+    /// If the failure is a maintenance hold rather than a malfunction.
+    #[serde(skip)]
+    pub maintenance: bool,
+    /// If the failure drains the service rather than marking it as down.
+    #[serde(skip)]
+    pub drain: bool,
+    /// The individual maintenance holds of the check, which are
+    /// listed as failing checks instead of this failure if present.
+    #[serde(skip)]
+    pub holds: Vec<MaintenanceHold>,
+}
+
+/// The consecutive results of a check. Only one of the
+/// counts is non-zero, depending on the latest result.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, serde::Deserialize)]
+pub(crate) struct CheckStreak {
+    /// The amount of consecutive failed results.
+    pub failures: u32,
+    /// The amount of consecutive successful results.
+    pub successes: u32,
+}
+
+/// The reported result of a single check in a check cycle.
+#[derive(Clone, Debug)]
+pub(crate) struct ReportedCheckResult {
+    /// The id of the check.
+    pub check_id: String,
+    /// The name of the check.
+    pub check_name: String,
+    /// The failure of the check, if the check is considered as failing.
+    pub failure: Option<FailingCheck>,
+    /// If the results of all other checks should be ignored.
+    pub ignore_other_results: bool,
+    /// The consecutive results of the check, if the check recorded a result.
+    pub streak: Option<CheckStreak>,
+}
+
+/// The reported results of all checks in a check cycle, from which
+/// the status of the service or a subset of its checks is derived.
+#[derive(Clone, Debug)]
+pub(crate) struct CheckCycleResults {
+    /// The timestamp when the checks were executed.
+    pub timestamp: Instant,
+    /// The results of the checks, in the order the checks are registered.
+    pub check_results: Vec<ReportedCheckResult>,
+}
+
+/// The status of a single check, regardless if the check failed.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct CheckStatus {
+    /// The id of the check.
+    pub check_id: String,
+    /// The name of the check.
+    pub check_name: String,
+    /// The severity of the failure of the check, ok if the check passed.
+    pub severity: CheckSeverity,
+    /// A descriptive reason why the check failed, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    /// The consecutive results of the check, if the check recorded a result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streak: Option<CheckStreak>,
+}
+
+/// The state of the service, derived from the check results. Each state
+/// is answered with its own (configurable) response code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ServiceState {
+    /// All checks passed.
+    Up,
+    /// Some checks failed with a warning, but none critically.
+    Degraded,
+    /// A drain hold marks the service as unavailable for new connections,
+    /// while existing connections are continued.
+    Drain,
+    /// A maintenance hold marks the service as unavailable.
+    Maintenance,
+    /// A check failed critically.
+    Down,
+}
+
+impl Display for ServiceState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceState::Up => write!(f, "up"),
+            ServiceState::Degraded => write!(f, "degraded"),
+            ServiceState::Drain => write!(f, "drain"),
+            ServiceState::Maintenance => write!(f, "maintenance"),
+            ServiceState::Down => write!(f, "down"),
+        }
+    }
+}
+
+impl FromStr for ServiceState {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "up" => Ok(ServiceState::Up),
+            "degraded" => Ok(ServiceState::Degraded),
+            "drain" => Ok(ServiceState::Drain),
+            "maintenance" => Ok(ServiceState::Maintenance),
+            "down" => Ok(ServiceState::Down),
+            state => Err(format!("unknown service state {}", state)),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct StatusCheckResults {
     /// The timestamp when the checks were last executed.
     pub timestamp: Instant,
-    /// The current response code that should be sent back
-    /// by the check endpoint to the requesting client.
-    pub api_response_code: StatusCode,
-    /// The checks that failed and lead to the changed response
-    /// code. If empty, the response code should be 200.
+    /// The state of the service, which determines the response
+    /// code sent back by the check endpoint to the requesting client.
+    pub state: ServiceState,
+    /// The highest severity of all failing checks, ok if there are none.
+    pub severity: CheckSeverity,
+    /// The checks that failed and lead to the changed state.
+    /// If empty, the service is up.
     pub failing_checks: Vec<FailingCheck>,
+    /// The status of all checks the results are derived from.
+    pub checks: Vec<CheckStatus>,
+}
+
+/// The failure reason of checks which did not produce a result yet.
+pub(crate) const PENDING_FAILURE_REASON: &str = "pending: waiting for the first result";
+
+fn default_failure_severity() -> CheckSeverity {
+    CheckSeverity::Critical
 }
 
 impl FailingCheck {
-    /// Constructs a new initially failed check status. This status
-    /// is only used during the period of constructing the status
-    /// manager and the first time the status checks are executed.
-    pub(super) fn new_initial_failed() -> Self {
-        Self {
-            check_name: String::from("Initial Check"),
-            failure_reason: String::from("Cannot determine status: checks weren't executed yet"),
-        }
+    /// Constructs a new failing check info for the given status checker
+    /// which did not produce a result yet.
+    #[allow(clippy::borrowed_box)]
+    pub fn new_pending(checker: &Box<dyn StatusChecker>) -> Self {
+        Self::new_from_check(checker, String::from(PENDING_FAILURE_REASON))
     }
 
     /// Constructs a new failing check info based on the given status
@@ -52,33 +183,244 @@ impl FailingCheck {
         Self {
             check_name: checker.check_name(),
             failure_reason,
+            severity: CheckSeverity::Critical,
+            perfdata: vec![],
+            streak: None,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
         }
     }
+
+    /// Sets the severity of this failing check.
+    pub fn with_severity(self, severity: CheckSeverity) -> Self {
+        Self { severity, ..self }
+    }
+
+    /// Attaches the current consecutive results of the check to this failing check.
+    pub fn with_streak(self, streak: CheckStreak) -> Self {
+        Self {
+            streak: Some(streak),
+            ..self
+        }
+    }
+
+    /// Sets if this failing check is a maintenance hold.
+    pub fn with_maintenance(self, maintenance: bool) -> Self {
+        Self {
+            maintenance,
+            ..self
+        }
+    }
+
+    /// Sets if this failing check drains the service.
+    pub fn with_drain(self, drain: bool) -> Self {
+        Self { drain, ..self }
+    }
+
+    /// Attaches the individual maintenance holds of the check to this failing check.
+    pub fn with_holds(self, holds: Vec<MaintenanceHold>) -> Self {
+        Self { holds, ..self }
+    }
+
+    /// Attaches the measured values reported by the check to this failing check.
+    pub fn with_perfdata(self, perfdata: Vec<PerformanceData>) -> Self {
+        Self { perfdata, ..self }
+    }
+
+    /// Returns the failing checks to list for this failure, which are the
+    /// individual maintenance holds if present or this failure otherwise.
+    pub fn listed_failures(&self) -> Vec<FailingCheck> {
+        if self.holds.is_empty() {
+            return vec![self.clone()];
+        }
+        self.holds
+            .iter()
+            .map(|hold| FailingCheck {
+                check_name: hold.name.clone(),
+                failure_reason: hold.reason.clone(),
+                holds: vec![],
+                ..self.clone()
+            })
+            .collect()
+    }
 }
 
-impl StatusHolder {
-    /// Creates a new status holder instance that has the initial check
-    /// status set to failed.
-    pub(super) fn new_initial_failed() -> Self {
-        let initial_check_result = StatusCheckResults {
+impl CheckCycleResults {
+    /// Constructs new check cycle results from the given check results.
+    pub fn new(check_results: Vec<ReportedCheckResult>) -> Self {
+        Self {
             timestamp: Instant::now(),
-            api_response_code: StatusCode::SERVICE_UNAVAILABLE,
-            failing_checks: vec![FailingCheck::new_initial_failed()],
+            check_results,
+        }
+    }
+
+    /// Derives the status from the results of the checks with the given ids, or
+    /// of all checks if no ids are given. Only the results of the selected checks
+    /// can cause the results of other checks to be ignored.
+    pub fn status_of(&self, check_ids: Option<&[String]>) -> StatusCheckResults {
+        let mut failed_checks: Vec<FailingCheck> = vec![];
+        let selected_results = self.check_results.iter().filter(|check_result| {
+            check_ids.map_or(true, |check_ids| check_ids.contains(&check_result.check_id))
+        });
+        for check_result in selected_results {
+            match &check_result.failure {
+                // the check failed and all other checks should be skipped, only
+                // return this failure reason
+                Some(failing_check) if check_result.ignore_other_results => {
+                    failed_checks = failing_check.listed_failures();
+                    break;
+                }
+                // the check failed but other checks shouldn't be skipped,
+                // register the failure reason and continue
+                Some(failing_check) => failed_checks.extend(failing_check.listed_failures()),
+                // the check was successful and all other results should be skipped,
+                // remove all failure reasons and use the successful result
+                None if check_result.ignore_other_results => {
+                    failed_checks.clear();
+                    break;
+                }
+                // the check was successful and other checks should be considered as well,
+                // just continue looking at the other results
+                None => {}
+            }
+        }
+
+        let checks = self
+            .check_results
+            .iter()
+            .filter(|check_result| {
+                check_ids.map_or(true, |check_ids| check_ids.contains(&check_result.check_id))
+            })
+            .map(|check_result| CheckStatus {
+                check_id: check_result.check_id.clone(),
+                check_name: check_result.check_name.clone(),
+                severity: check_result
+                    .failure
+                    .as_ref()
+                    .map_or(CheckSeverity::Ok, |failure| failure.severity),
+                failure_reason: check_result
+                    .failure
+                    .as_ref()
+                    .map(|failure| failure.failure_reason.clone()),
+                streak: check_result.streak,
+            })
+            .collect();
+        // a maintenance hold takes precedence over the failures of other checks, a
+        // drain hold only applies as long as no other check fails critically
+        let maintenance = failed_checks
+            .iter()
+            .any(|failing_check| failing_check.maintenance);
+        let drain = failed_checks
+            .iter()
+            .any(|failing_check| failing_check.drain);
+        let down = failed_checks.iter().any(|failing_check| {
+            !failing_check.drain && failing_check.severity == CheckSeverity::Critical
+        });
+        let status = StatusCheckResults::from_failing_checks(failed_checks);
+        StatusCheckResults {
+            timestamp: self.timestamp,
+            state: if maintenance {
+                ServiceState::Maintenance
+            } else if drain && !down {
+                ServiceState::Drain
+            } else {
+                status.state
+            },
+            checks,
+            ..status
+        }
+    }
+}
+
+impl StatusCheckResults {
+    /// Constructs new check results from the given failing checks. Only critical
+    /// failures mark the service as down, warnings mark the service as degraded.
+    pub fn from_failing_checks(failing_checks: Vec<FailingCheck>) -> Self {
+        let severity = failing_checks
+            .iter()
+            .map(|failing_check| failing_check.severity)
+            .max()
+            .unwrap_or(CheckSeverity::Ok);
+        let state = match severity {
+            CheckSeverity::Ok => ServiceState::Up,
+            CheckSeverity::Warning => ServiceState::Degraded,
+            CheckSeverity::Critical => ServiceState::Down,
         };
-        let status = Arc::new(RwLock::new(initial_check_result));
         Self {
-            current_status: status,
+            timestamp: Instant::now(),
+            state,
+            severity,
+            failing_checks,
+            checks: vec![],
+        }
+    }
+
+    /// Marks these results as stale, which marks the service as down. The
+    /// previously failing checks are kept after the stale status check.
+    fn into_stale(self, max_result_age: Duration) -> Self {
+        let failure_reason = format!(
+            "check results are {}s old, maximum age is {}s",
+            self.timestamp.elapsed().as_secs(),
+            max_result_age.as_secs()
+        );
+        let stale_check = FailingCheck {
+            check_name: String::from("status stale"),
+            failure_reason,
+            severity: CheckSeverity::Critical,
+            perfdata: vec![],
+            streak: None,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
+        };
+        let mut failing_checks = vec![stale_check];
+        failing_checks.extend(self.failing_checks);
+        Self {
+            timestamp: self.timestamp,
+            state: ServiceState::Down,
+            severity: CheckSeverity::Critical,
+            failing_checks,
+            checks: self.checks,
         }
     }
+}
 
-    /// Reads the current status check result.
+impl StatusHolder {
+    /// Creates a new status holder instance that initially holds the
+    /// given results, which are based on the initial state of the checks.
+    pub(super) fn new(
+        initial_check_results: CheckCycleResults,
+        max_result_age: Option<Duration>,
+    ) -> Self {
+        let results = Arc::new(RwLock::new(initial_check_results));
+        Self {
+            current_results: results,
+            max_result_age,
+        }
+    }
+
+    /// Reads the current status check result of all checks.
     pub async fn current_status(&self) -> StatusCheckResults {
-        self.current_status.read().await.clone()
+        self.current_status_of(None).await
+    }
+
+    /// Reads the current status check result of the checks with the given ids, or of
+    /// all checks if no ids are given. If the result is older than the maximum result
+    /// age, a stale result is returned instead.
+    pub async fn current_status_of(&self, check_ids: Option<&[String]>) -> StatusCheckResults {
+        let current_status = self.current_results.read().await.status_of(check_ids);
+        match self.max_result_age {
+            Some(max_result_age) if current_status.timestamp.elapsed() > max_result_age => {
+                current_status.into_stale(max_result_age)
+            }
+            _ => current_status,
+        }
     }
 
-    /// Sets the current status check result.
-    pub(super) async fn update_current_status(&self, check_results: StatusCheckResults) {
-        let mut check_result_write_guard = self.current_status.write().await;
+    /// Sets the results of the checks in the current check cycle.
+    pub(super) async fn update_current_results(&self, check_results: CheckCycleResults) {
+        let mut check_result_write_guard = self.current_results.write().await;
         *check_result_write_guard = check_results;
     }
 }
@@ -87,47 +429,222 @@ impl StatusHolder {
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn initial_state_is_503_with_initial_check() {
-        let holder = StatusHolder::new_initial_failed();
-        let status = holder.current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::SERVICE_UNAVAILABLE);
-        assert_eq!(status.failing_checks.len(), 1);
-        assert_eq!(status.failing_checks[0].check_name, "Initial Check");
+    fn failing_check(severity: CheckSeverity) -> FailingCheck {
+        FailingCheck {
+            check_name: "test check".to_string(),
+            failure_reason: "it broke".to_string(),
+            severity,
+            perfdata: vec![],
+            streak: None,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
+        }
+    }
+
+    #[test]
+    fn results_without_failures_are_up() {
+        let results = StatusCheckResults::from_failing_checks(vec![]);
+        assert_eq!(results.state, ServiceState::Up);
+        assert_eq!(results.severity, CheckSeverity::Ok);
+    }
+
+    #[test]
+    fn results_with_warnings_only_are_degraded() {
+        let results =
+            StatusCheckResults::from_failing_checks(vec![failing_check(CheckSeverity::Warning)]);
+        assert_eq!(results.state, ServiceState::Degraded);
+        assert_eq!(results.severity, CheckSeverity::Warning);
+    }
+
+    #[test]
+    fn results_with_critical_failure_are_down() {
+        let results = StatusCheckResults::from_failing_checks(vec![
+            failing_check(CheckSeverity::Warning),
+            failing_check(CheckSeverity::Critical),
+        ]);
+        assert_eq!(results.state, ServiceState::Down);
+        assert_eq!(results.severity, CheckSeverity::Critical);
+    }
+
+    fn check_result(check_id: &str, failure: Option<FailingCheck>) -> ReportedCheckResult {
+        ReportedCheckResult {
+            check_id: check_id.to_string(),
+            check_name: format!("{} check", check_id),
+            failure,
+            ignore_other_results: false,
+            streak: None,
+        }
+    }
+
+    fn cycle_results(failing_checks: Vec<FailingCheck>) -> CheckCycleResults {
+        CheckCycleResults::new(
+            failing_checks
+                .into_iter()
+                .map(|failing_check| check_result("test", Some(failing_check)))
+                .collect(),
+        )
     }
 
     #[tokio::test]
     async fn update_to_healthy_returns_200_empty_failures() {
-        let holder = StatusHolder::new_initial_failed();
-        let healthy = StatusCheckResults {
-            timestamp: Instant::now(),
-            api_response_code: StatusCode::OK,
-            failing_checks: vec![],
-        };
-        holder.update_current_status(healthy).await;
+        let holder = StatusHolder::new(
+            cycle_results(vec![failing_check(CheckSeverity::Critical)]),
+            None,
+        );
+        holder.update_current_results(cycle_results(vec![])).await;
 
         let status = holder.current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::OK);
+        assert_eq!(status.state, ServiceState::Up);
         assert!(status.failing_checks.is_empty());
     }
 
     #[tokio::test]
     async fn update_to_failing_returns_503_with_failure_info() {
-        let holder = StatusHolder::new_initial_failed();
-        let failing = StatusCheckResults {
-            timestamp: Instant::now(),
-            api_response_code: StatusCode::SERVICE_UNAVAILABLE,
-            failing_checks: vec![FailingCheck {
-                check_name: "test check".to_string(),
-                failure_reason: "it broke".to_string(),
-            }],
-        };
-        holder.update_current_status(failing).await;
+        let holder = StatusHolder::new(cycle_results(vec![]), None);
+        holder
+            .update_current_results(cycle_results(vec![failing_check(CheckSeverity::Critical)]))
+            .await;
 
         let status = holder.current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status.state, ServiceState::Down);
         assert_eq!(status.failing_checks.len(), 1);
         assert_eq!(status.failing_checks[0].check_name, "test check");
         assert_eq!(status.failing_checks[0].failure_reason, "it broke");
     }
+
+    #[tokio::test]
+    async fn stale_result_returns_503_with_stale_check() {
+        let holder = StatusHolder::new(
+            cycle_results(vec![failing_check(CheckSeverity::Warning)]),
+            Some(Duration::from_millis(50)),
+        );
+        let status = holder.current_status().await;
+        assert_eq!(status.state, ServiceState::Degraded);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let status = holder.current_status().await;
+        assert_eq!(status.state, ServiceState::Down);
+        assert_eq!(status.severity, CheckSeverity::Critical);
+        assert_eq!(status.failing_checks.len(), 2);
+        assert_eq!(status.failing_checks[0].check_name, "status stale");
+        assert_eq!(status.failing_checks[1].check_name, "test check");
+
+        holder.update_current_results(cycle_results(vec![])).await;
+        let status = holder.current_status().await;
+        assert_eq!(status.state, ServiceState::Up);
+    }
+
+    #[test]
+    fn status_of_only_considers_given_checks() {
+        let results = CheckCycleResults::new(vec![
+            check_result("http", Some(failing_check(CheckSeverity::Critical))),
+            check_result("disk", None),
+        ]);
+        let status = results.status_of(Some(&["disk".to_string()]));
+        assert_eq!(status.state, ServiceState::Up);
+        assert_eq!(status.timestamp, results.timestamp);
+
+        let status = results.status_of(None);
+        assert_eq!(status.state, ServiceState::Down);
+        assert_eq!(status.checks.len(), 2);
+        assert_eq!(status.checks[0].check_id, "http");
+        assert_eq!(status.checks[0].severity, CheckSeverity::Critical);
+        assert_eq!(status.checks[1].check_id, "disk");
+        assert_eq!(status.checks[1].severity, CheckSeverity::Ok);
+        assert_eq!(status.checks[1].failure_reason, None);
+    }
+
+    #[test]
+    fn status_of_ignores_other_results_of_selected_checks_only() {
+        let results = CheckCycleResults::new(vec![
+            ReportedCheckResult {
+                check_id: "force-success".to_string(),
+                check_name: "force success check".to_string(),
+                failure: None,
+                ignore_other_results: true,
+                streak: None,
+            },
+            check_result("http", Some(failing_check(CheckSeverity::Critical))),
+        ]);
+        let status = results.status_of(None);
+        assert_eq!(status.state, ServiceState::Up);
+
+        let status = results.status_of(Some(&["http".to_string()]));
+        assert_eq!(status.state, ServiceState::Down);
+    }
+
+    #[test]
+    fn status_of_lists_individual_holds() {
+        let holds = vec![
+            MaintenanceHold {
+                name: "mtc file dba".to_string(),
+                reason: "mtc file exists".to_string(),
+            },
+            MaintenanceHold {
+                name: "mtc file deploy".to_string(),
+                reason: "mtc file exists: release".to_string(),
+            },
+        ];
+        let failure = failing_check(CheckSeverity::Critical)
+            .with_maintenance(true)
+            .with_holds(holds);
+        let status = cycle_results(vec![failure]).status_of(None);
+        assert_eq!(status.state, ServiceState::Maintenance);
+        assert_eq!(status.failing_checks.len(), 2);
+        assert_eq!(status.failing_checks[0].check_name, "mtc file dba");
+        assert_eq!(status.failing_checks[1].check_name, "mtc file deploy");
+        assert_eq!(
+            status.failing_checks[1].failure_reason,
+            "mtc file exists: release"
+        );
+        assert!(status.failing_checks[1].maintenance);
+        assert_eq!(status.checks.len(), 1);
+    }
+
+    #[test]
+    fn maintenance_hold_takes_precedence() {
+        let results = CheckCycleResults::new(vec![
+            check_result("http", Some(failing_check(CheckSeverity::Critical))),
+            check_result(
+                "mtc",
+                Some(failing_check(CheckSeverity::Critical).with_maintenance(true)),
+            ),
+        ]);
+        assert_eq!(results.status_of(None).state, ServiceState::Maintenance);
+        let status = results.status_of(Some(&["http".to_string()]));
+        assert_eq!(status.state, ServiceState::Down);
+    }
+
+    #[test]
+    fn drain_hold_only_applies_without_critical_failures() {
+        let drain = || Some(failing_check(CheckSeverity::Critical).with_drain(true));
+        let results = CheckCycleResults::new(vec![
+            check_result("drain", drain()),
+            check_result("disk", Some(failing_check(CheckSeverity::Warning))),
+        ]);
+        assert_eq!(results.status_of(None).state, ServiceState::Drain);
+
+        let results = CheckCycleResults::new(vec![
+            check_result("drain", drain()),
+            check_result("http", Some(failing_check(CheckSeverity::Critical))),
+        ]);
+        assert_eq!(results.status_of(None).state, ServiceState::Down);
+
+        let results = CheckCycleResults::new(vec![
+            check_result("drain", drain()),
+            check_result(
+                "mtc",
+                Some(failing_check(CheckSeverity::Critical).with_maintenance(true)),
+            ),
+        ]);
+        assert_eq!(results.status_of(None).state, ServiceState::Maintenance);
+    }
+
+    #[test]
+    fn failing_check_without_severity_is_critical() {
+        let failing_check: FailingCheck =
+            serde_json::from_str(r#"{"check_name": "a", "failure_reason": "b"}"#).unwrap();
+        assert_eq!(failing_check.severity, CheckSeverity::Critical);
+    }
 }
@@ -1,12 +1,31 @@
+use crate::checks::disk_space_check::DiskSpaceCheck;
+use crate::checks::drain_file_check::DrainFileCheck;
+use crate::checks::exec_check::ExecCheck;
+use crate::checks::file_content_check::FileContentCheck;
+use crate::checks::file_freshness_check::FileFreshnessCheck;
 use crate::checks::force_success_file_check::ForceSuccessFileCheck;
 use crate::checks::http_response_check::HttpResponseCheck;
+use crate::checks::memory_check::MemoryCheck;
 use crate::checks::mtc_file_check::MtcFileCheck;
 use crate::checks::network_connection_check::NetworkConnectionCheck;
+use crate::checks::pressure_check::PressureCheck;
+use crate::checks::process_check::ProcessCheck;
+use crate::checks::remote_easycheck_check::RemoteEasycheckCheck;
 use crate::options::Options;
-use crate::status::status_checker::StatusChecker;
-use crate::status::status_holder::{FailingCheck, StatusCheckResults, StatusHolder};
-use axum::http::StatusCode;
+use crate::status::check_dependencies::execution_waves;
+use crate::status::check_group::CheckGroup;
+use crate::status::check_settings::{CheckInitialState, CheckSettings};
+use crate::status::check_state::CheckState;
+use crate::status::status_checker::{CheckSeverity, StatusCheckResult, StatusChecker};
+use crate::status::status_endpoint::StatusEndpoint;
+use crate::status::status_holder::{
+    CheckCycleResults, FailingCheck, ReportedCheckResult, StatusHolder,
+};
+use crate::util::file_watcher::WatchedPath;
 use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 use tokio::time::Instant;
 
 /// The managing service for status checks.
@@ -14,22 +33,41 @@ pub(crate) struct StatusManager {
     /// The status checkers that should be executed periodically
     /// to determine the status of the current instance.
     status_checker: Vec<Box<dyn StatusChecker>>,
+    /// The settings how the results of the checks are evaluated, by check id.
+    check_settings: HashMap<String, CheckSettings>,
+    /// The indexes of the status checkers in the order they are executed, checks of
+    /// the same wave are executed in parallel after the checks they depend on.
+    execution_waves: Vec<Vec<usize>>,
+    /// The groups of checks that are evaluated as a unit.
+    check_groups: Vec<CheckGroup>,
+    /// The http endpoints serving the status derived from the checks.
+    status_endpoints: Vec<StatusEndpoint>,
+    /// The state of the checks across check cycles, by check id.
+    check_states: Mutex<HashMap<String, CheckState>>,
+    /// The time when this manager was constructed.
+    started_at: Instant,
+    /// The duration after startup during which failures of checks that did not
+    /// succeed yet are ignored, and the initial state of the check is used instead.
+    startup_grace_period: Duration,
     /// The holder for the current check status.
     status_holder: StatusHolder,
+    /// The results of the latest check cycle before the evaluation of check groups,
+    /// which are reused if only the checks watching changed files are re-executed.
+    latest_results: Mutex<Option<CheckCycleResults>>,
 }
 
 impl StatusManager {
-    /// Registers a status checker into the given vec in case the construction
-    /// was successful and the checker had all options present to be enabled.
+    /// Registers the status checkers into the given vec in case the construction
+    /// was successful and the checkers had all options present to be enabled.
     /// If a construction error occurred, the error is returned to the caller.
     fn register_checker_if_enabled<S>(
         status_checker: &mut Vec<Box<dyn StatusChecker>>,
-        checker_construct_result: anyhow::Result<Option<S>>,
+        checker_construct_result: anyhow::Result<Vec<S>>,
     ) -> anyhow::Result<()>
     where
         S: StatusChecker + 'static,
     {
-        if let Some(checker) = checker_construct_result? {
+        for checker in checker_construct_result? {
             status_checker.push(Box::new(checker));
         }
 
@@ -44,27 +82,95 @@ impl StatusManager {
         let mut status_checker: Vec<Box<dyn StatusChecker>> = vec![];
         Self::register_checker_if_enabled(
             &mut status_checker,
-            ForceSuccessFileCheck::from_options(options),
+            ForceSuccessFileCheck::all_from_options(options),
         )?;
         Self::register_checker_if_enabled(
             &mut status_checker,
-            MtcFileCheck::from_options(options),
+            MtcFileCheck::all_from_options(options),
         )?;
         Self::register_checker_if_enabled(
             &mut status_checker,
-            HttpResponseCheck::from_options(options),
+            DrainFileCheck::all_from_options(options),
         )?;
         Self::register_checker_if_enabled(
             &mut status_checker,
-            NetworkConnectionCheck::from_options(options),
+            HttpResponseCheck::all_from_options(options),
         )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            NetworkConnectionCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            DiskSpaceCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            MemoryCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            PressureCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            ProcessCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            FileFreshnessCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            FileContentCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            ExecCheck::all_from_options(options),
+        )?;
+        Self::register_checker_if_enabled(
+            &mut status_checker,
+            RemoteEasycheckCheck::all_from_options(options),
+        )?;
+
+        let check_settings = CheckSettings::from_options(options, &status_checker)?;
+        let check_groups = CheckGroup::from_options(options, &status_checker)?;
+        let execution_waves = execution_waves(&status_checker, &check_settings)?;
+        let status_endpoints = StatusEndpoint::from_options(options, &status_checker)?;
+
+        // the status until the first check cycle completed is based on the initial state of the checks
+        let initial_check_results = Self::initial_check_results(&status_checker, &check_settings);
 
         Ok(Self {
             status_checker,
-            status_holder: StatusHolder::new_initial_failed(),
+            check_settings,
+            execution_waves,
+            check_groups,
+            status_endpoints,
+            check_states: Mutex::new(HashMap::new()),
+            started_at: Instant::now(),
+            startup_grace_period: Duration::from_secs(options.startup_grace_period_seconds),
+            status_holder: StatusHolder::new(
+                initial_check_results,
+                options.max_result_age_seconds.map(Duration::from_secs),
+            ),
+            latest_results: Mutex::new(None),
         })
     }
 
+    /// Returns the http endpoints serving the status derived from the checks.
+    pub(crate) fn status_endpoints(&self) -> Vec<StatusEndpoint> {
+        self.status_endpoints.clone()
+    }
+
+    /// Returns the paths whose changes should immediately trigger a check cycle.
+    pub(crate) fn watched_paths(&self) -> Vec<WatchedPath> {
+        self.status_checker
+            .iter()
+            .flat_map(|checker| checker.watched_paths())
+            .collect()
+    }
+
     /// Returns a cloned instance of the status holder used by this manager.
     pub(crate) fn status_holder(&self) -> StatusHolder {
         self.status_holder.clone()
@@ -73,88 +179,300 @@ impl StatusManager {
     /// Executes all registered status checks and sets the current
     /// status based on their execution results.
     pub async fn execute_status_checks(&self) {
-        // execute all status checks in parallel
-        let mut failed_checks: Vec<FailingCheck> = vec![];
-        let check_futures: Vec<_> = self
+        let results_by_check = self.status_checker.iter().map(|_| None).collect();
+        let check_results = self.execute_checks(results_by_check).await;
+        self.publish_results(CheckCycleResults::new(check_results))
+            .await;
+    }
+
+    /// Re-executes only the checks watching one of the given changed paths, and sets the
+    /// current status based on their results and the results of the other checks in the
+    /// latest check cycle. The results keep the timestamp of the latest check cycle, as
+    /// the other checks were not executed. Checks depending on the re-executed checks are
+    /// only re-evaluated in the next check cycle.
+    pub async fn execute_checks_watching(&self, changed_paths: &[WatchedPath]) {
+        let latest_results = self.latest_results.lock().unwrap().clone();
+        let Some(latest_results) = latest_results else {
+            // no check cycle completed yet, there are no results of the other checks
+            return self.execute_status_checks().await;
+        };
+        let results_by_check: Vec<Option<ReportedCheckResult>> = self
             .status_checker
             .iter()
-            .map(|checker| checker.execute_check())
+            .zip(latest_results.check_results)
+            .map(|(checker, latest_result)| {
+                let watches_changed_path = checker
+                    .watched_paths()
+                    .iter()
+                    .any(|watched_path| changed_paths.contains(watched_path));
+                (!watches_changed_path).then_some(latest_result)
+            })
             .collect();
-        let results = join_all(check_futures).await;
-        log::debug!("executed {} status checks", results.len());
-        for (checker, result) in self.status_checker.iter().zip(results) {
-            match result {
-                Ok(check_result) => {
-                    log::debug!(
-                        "check '{}': failure_reason={:?}, ignore_other_results={}",
-                        checker.check_name(),
-                        check_result.failure_reason,
-                        check_result.ignore_other_results
-                    );
-                    match check_result.failure_reason {
-                        // failure reason is present and all other checks should be skipped, only
-                        // return this failure reason
-                        Some(failure_reason) if check_result.ignore_other_results => {
-                            let failing_check =
-                                FailingCheck::new_from_check(checker, failure_reason);
-                            failed_checks = vec![failing_check];
-                            break;
-                        }
-                        // failure reason is present but other checks shouldn't be skipped,
-                        // register the failure reason and continue
-                        Some(failure_reason) => {
-                            let failing_check =
-                                FailingCheck::new_from_check(checker, failure_reason);
-                            failed_checks.push(failing_check);
-                        }
-                        // the check was successful and all other results should be skipped,
-                        // remove all failure reasons and use the successful result
-                        None if check_result.ignore_other_results => {
-                            failed_checks.clear();
-                            break;
-                        }
-                        // the check was successful and other checks should be considered as well,
-                        // just continue looking at the other results
-                        None => {}
-                    }
+        if results_by_check.iter().all(Option::is_some) {
+            return;
+        }
+
+        let check_results = self.execute_checks(results_by_check).await;
+        self.publish_results(CheckCycleResults {
+            timestamp: latest_results.timestamp,
+            check_results,
+        })
+        .await;
+    }
+
+    /// Executes the checks without a given result, in the order of the execution waves.
+    /// Returns the results of all checks, in the order the checks are registered.
+    async fn execute_checks(
+        &self,
+        mut results_by_check: Vec<Option<ReportedCheckResult>>,
+    ) -> Vec<ReportedCheckResult> {
+        let in_grace_period = self.started_at.elapsed() < self.startup_grace_period;
+        for wave in &self.execution_waves {
+            // checks with a failed dependency are skipped, the other checks
+            // of the wave are executed in parallel
+            let mut executed_checks: Vec<usize> = vec![];
+            for &index in wave {
+                if results_by_check[index].is_some() {
+                    continue;
                 }
-                Err(ref error) => {
-                    log::debug!("check '{}' errored: {}", checker.check_name(), error);
-                    // checker failed with an error, assume it's an issue that makes the backend be down
-                    let failure_reason = format!("check failed with error: {}", error);
-                    let failing_check = FailingCheck::new_from_check(checker, failure_reason);
-                    failed_checks.push(failing_check);
+                let checker = &self.status_checker[index];
+                match self.failed_dependency(checker, &results_by_check) {
+                    Some(dependency) => {
+                        log::debug!(
+                            "skipping check '{}', dependency {} failed",
+                            checker.check_name(),
+                            dependency
+                        );
+                        let failure_reason = format!("skipped: dependency {} failed", dependency);
+                        let severity =
+                            CheckSeverity::Critical.min(self.settings_of(checker).max_severity);
+                        let failure = FailingCheck::new_from_check(checker, failure_reason)
+                            .with_severity(severity);
+                        results_by_check[index] = Some(ReportedCheckResult {
+                            check_id: checker.check_id().to_string(),
+                            check_name: checker.check_name(),
+                            failure: Some(failure),
+                            ignore_other_results: false,
+                            streak: None,
+                        });
+                    }
+                    None => executed_checks.push(index),
                 }
             }
-        }
+            let check_futures: Vec<_> = executed_checks
+                .iter()
+                .map(|&index| self.status_checker[index].execute_check())
+                .collect();
+            let results = join_all(check_futures).await;
+            log::debug!("executed {} status checks", results.len());
 
-        let check_results = if failed_checks.is_empty() {
-            // there are no failed checks, assume all services are ready
-            StatusCheckResults {
-                timestamp: Instant::now(),
-                api_response_code: StatusCode::OK,
-                failing_checks: vec![],
+            // record the results in the check states, which only report a changed status
+            // after the configured amount of consecutive equal results
+            let mut check_states = self.check_states.lock().unwrap();
+            for (index, result) in executed_checks.into_iter().zip(results) {
+                results_by_check[index] = Some(self.record_check_result(
+                    &self.status_checker[index],
+                    result,
+                    in_grace_period,
+                    &mut check_states,
+                ));
             }
-        } else {
-            // failed checks are present, assume it's down
-            StatusCheckResults {
-                timestamp: Instant::now(),
-                api_response_code: StatusCode::SERVICE_UNAVAILABLE,
-                failing_checks: failed_checks,
-            }
-        };
+        }
+        results_by_check.into_iter().flatten().collect()
+    }
+
+    /// Evaluates the check groups on the given results and writes them into the current
+    /// status. The results are kept to re-execute only the checks watching changed files.
+    async fn publish_results(&self, latest_results: CheckCycleResults) {
+        *self.latest_results.lock().unwrap() = Some(latest_results.clone());
+
+        // checks in a group are evaluated as a unit, failures of the members
+        // only mark the service as down if the policy of the group is missed
+        let mut check_results = latest_results;
+        for check_group in &self.check_groups {
+            let member_failures = check_results
+                .check_results
+                .iter_mut()
+                .filter(|check_result| check_group.check_ids.contains(&check_result.check_id))
+                .filter_map(|check_result| check_result.failure.as_mut())
+                .collect();
+            check_group.evaluate(member_failures);
+        }
 
         // write the check results into the current status
         self.status_holder
-            .update_current_status(check_results)
+            .update_current_results(check_results)
             .await;
     }
+
+    /// Returns the results of the given checks based on their initial state.
+    fn initial_check_results(
+        status_checker: &[Box<dyn StatusChecker>],
+        check_settings: &HashMap<String, CheckSettings>,
+    ) -> CheckCycleResults {
+        let default_settings = CheckSettings::default();
+        let check_results = status_checker
+            .iter()
+            .map(|checker| {
+                let settings = check_settings
+                    .get(checker.check_id())
+                    .unwrap_or(&default_settings);
+                ReportedCheckResult {
+                    check_id: checker.check_id().to_string(),
+                    check_name: checker.check_name(),
+                    failure: Self::initial_failure(checker, settings, None),
+                    ignore_other_results: false,
+                    streak: None,
+                }
+            })
+            .collect();
+        CheckCycleResults::new(check_results)
+    }
+
+    /// Returns the settings of the given check, or the default settings if none are present.
+    #[allow(clippy::borrowed_box)]
+    fn settings_of(&self, checker: &Box<dyn StatusChecker>) -> &CheckSettings {
+        static DEFAULT_SETTINGS: OnceLock<CheckSettings> = OnceLock::new();
+        self.check_settings
+            .get(checker.check_id())
+            .unwrap_or_else(|| DEFAULT_SETTINGS.get_or_init(CheckSettings::default))
+    }
+
+    /// Returns the id of the first dependency of the given check that failed critically
+    /// (or was skipped itself), based on the results of the previously executed checks.
+    #[allow(clippy::borrowed_box)]
+    fn failed_dependency<'a>(
+        &'a self,
+        checker: &Box<dyn StatusChecker>,
+        results_by_check: &[Option<ReportedCheckResult>],
+    ) -> Option<&'a str> {
+        self.settings_of(checker)
+            .depends_on
+            .iter()
+            .find(|dependency| {
+                results_by_check.iter().flatten().any(|result| {
+                    result.check_id == **dependency
+                        && result
+                            .failure
+                            .as_ref()
+                            .is_some_and(|failure| failure.severity == CheckSeverity::Critical)
+                })
+            })
+            .map(|dependency| dependency.as_str())
+    }
+
+    /// Records the given result of a check in its state. Returns the result to report
+    /// for the check, including its failure and if other results should be ignored.
+    #[allow(clippy::borrowed_box)]
+    fn record_check_result(
+        &self,
+        checker: &Box<dyn StatusChecker>,
+        result: anyhow::Result<StatusCheckResult>,
+        in_grace_period: bool,
+        check_states: &mut HashMap<String, CheckState>,
+    ) -> ReportedCheckResult {
+        let settings = self.settings_of(checker);
+        let (failure, ignore_other_results) =
+            Self::evaluate_check_result(checker, result, settings);
+        let failed = failure.is_some();
+        let check_state = check_states
+            .entry(checker.check_id().to_string())
+            .or_default();
+        let reported_failure = if in_grace_period && failed && !check_state.has_result() {
+            // failures during the startup grace period are only considered after
+            // the check succeeded once, until then the initial state is used
+            Self::initial_failure(checker, settings, failure)
+        } else {
+            check_state.record(failure, settings)
+        };
+        // other results are only ignored if the reported status matches the result
+        let ignore_other_results = ignore_other_results && failed == reported_failure.is_some();
+        ReportedCheckResult {
+            check_id: checker.check_id().to_string(),
+            check_name: checker.check_name(),
+            failure: reported_failure,
+            ignore_other_results,
+            streak: check_state.streak(),
+        }
+    }
+
+    /// Returns the failure to report for the given check based on its initial state.
+    /// The given failure is reported for checks that are initially down, if present.
+    #[allow(clippy::borrowed_box)]
+    fn initial_failure(
+        checker: &Box<dyn StatusChecker>,
+        settings: &CheckSettings,
+        failure: Option<FailingCheck>,
+    ) -> Option<FailingCheck> {
+        let severity = CheckSeverity::Critical.min(settings.max_severity);
+        match settings.initial_state {
+            CheckInitialState::Up => {
+                if let Some(failure) = failure {
+                    log::debug!(
+                        "ignoring failure of '{}' during startup: {}",
+                        failure.check_name,
+                        failure.failure_reason
+                    );
+                }
+                None
+            }
+            CheckInitialState::Down => Some(failure.unwrap_or_else(|| {
+                let failure_reason = String::from("initial state is down");
+                FailingCheck::new_from_check(checker, failure_reason).with_severity(severity)
+            })),
+            CheckInitialState::Pending => {
+                Some(FailingCheck::new_pending(checker).with_severity(severity))
+            }
+        }
+    }
+
+    /// Converts the given result of a check into the failure of the check, if the
+    /// check failed. Returns the failure and if other results should be ignored.
+    #[allow(clippy::borrowed_box)]
+    fn evaluate_check_result(
+        checker: &Box<dyn StatusChecker>,
+        result: anyhow::Result<StatusCheckResult>,
+        settings: &CheckSettings,
+    ) -> (Option<FailingCheck>, bool) {
+        match result {
+            Ok(check_result) => {
+                let severity = check_result.severity.min(settings.max_severity);
+                log::debug!(
+                    "check '{}': failure_reason={:?}, severity={}, ignore_other_results={}",
+                    checker.check_name(),
+                    check_result.failure_reason,
+                    severity,
+                    check_result.ignore_other_results
+                );
+                let failure = check_result.failure_reason.map(|failure_reason| {
+                    FailingCheck::new_from_check(checker, failure_reason)
+                        .with_severity(severity)
+                        .with_maintenance(check_result.maintenance)
+                        .with_drain(check_result.drain || settings.drain)
+                        .with_holds(check_result.holds)
+                        .with_perfdata(check_result.perfdata)
+                });
+                (failure, check_result.ignore_other_results)
+            }
+            Err(error) => {
+                log::debug!("check '{}' errored: {}", checker.check_name(), error);
+                // checker failed with an error, assume it's an issue that makes the backend be down
+                let failure_reason = format!("check failed with error: {}", error);
+                let severity = CheckSeverity::Critical.min(settings.max_severity);
+                let failure = FailingCheck::new_from_check(checker, failure_reason)
+                    .with_severity(severity)
+                    .with_drain(settings.drain);
+                (Some(failure), false)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::status::status_checker::StatusCheckResult;
+    use crate::status::check_group::GroupPolicy;
+    use crate::status::status_holder::{CheckStreak, ServiceState, PENDING_FAILURE_REASON};
 
     struct SuccessChecker;
     #[async_trait::async_trait]
@@ -165,6 +483,9 @@ mod tests {
         fn check_name(&self) -> String {
             "success_checker".to_string()
         }
+        fn check_id(&self) -> &str {
+            "success"
+        }
         async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
             Ok(StatusCheckResult::new_success())
         }
@@ -179,11 +500,55 @@ mod tests {
         fn check_name(&self) -> String {
             "failure_checker".to_string()
         }
+        fn check_id(&self) -> &str {
+            "failure"
+        }
         async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
             Ok(StatusCheckResult::new_failure("always fails".to_string()))
         }
     }
 
+    struct WarningChecker;
+    #[async_trait::async_trait]
+    impl StatusChecker for WarningChecker {
+        fn from_options(_: &Options) -> anyhow::Result<Option<Self>> {
+            Ok(Some(Self))
+        }
+        fn check_name(&self) -> String {
+            "warning_checker".to_string()
+        }
+        fn check_id(&self) -> &str {
+            "warning"
+        }
+        async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+            Ok(StatusCheckResult::new_warning("almost broken".to_string()))
+        }
+    }
+
+    /// Returns the given results one after another, true being a success.
+    struct ScriptedChecker {
+        results: Mutex<Vec<bool>>,
+    }
+    #[async_trait::async_trait]
+    impl StatusChecker for ScriptedChecker {
+        fn from_options(_: &Options) -> anyhow::Result<Option<Self>> {
+            Ok(None)
+        }
+        fn check_name(&self) -> String {
+            "scripted_checker".to_string()
+        }
+        fn check_id(&self) -> &str {
+            "scripted"
+        }
+        async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+            if self.results.lock().unwrap().remove(0) {
+                Ok(StatusCheckResult::new_success())
+            } else {
+                Ok(StatusCheckResult::new_failure("flapping".to_string()))
+            }
+        }
+    }
+
     struct ErrorChecker;
     #[async_trait::async_trait]
     impl StatusChecker for ErrorChecker {
@@ -193,6 +558,9 @@ mod tests {
         fn check_name(&self) -> String {
             "error_checker".to_string()
         }
+        fn check_id(&self) -> &str {
+            "error"
+        }
         async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
             Err(anyhow::anyhow!("check exploded"))
         }
@@ -207,15 +575,55 @@ mod tests {
         fn check_name(&self) -> String {
             "force_success_checker".to_string()
         }
+        fn check_id(&self) -> &str {
+            "force-success"
+        }
         async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
             Ok(StatusCheckResult::new_success().ignore_other_results())
         }
     }
 
+    /// Fails while the watched file exists.
+    struct FileWatchingChecker {
+        file_path: std::path::PathBuf,
+    }
+    #[async_trait::async_trait]
+    impl StatusChecker for FileWatchingChecker {
+        fn from_options(_: &Options) -> anyhow::Result<Option<Self>> {
+            Ok(None)
+        }
+        fn check_name(&self) -> String {
+            "file_watching_checker".to_string()
+        }
+        fn check_id(&self) -> &str {
+            "file-watching"
+        }
+        fn watched_paths(&self) -> Vec<WatchedPath> {
+            vec![WatchedPath::File(self.file_path.clone())]
+        }
+        async fn execute_check(&self) -> anyhow::Result<StatusCheckResult> {
+            if self.file_path.exists() {
+                Ok(StatusCheckResult::new_failure("file exists".to_string()))
+            } else {
+                Ok(StatusCheckResult::new_success())
+            }
+        }
+    }
+
     fn make_manager(checkers: Vec<Box<dyn StatusChecker>>) -> StatusManager {
+        let initial_check_results =
+            StatusManager::initial_check_results(&checkers, &HashMap::new());
         StatusManager {
+            execution_waves: vec![(0..checkers.len()).collect()],
             status_checker: checkers,
-            status_holder: StatusHolder::new_initial_failed(),
+            check_settings: HashMap::new(),
+            check_groups: vec![],
+            status_endpoints: vec![],
+            check_states: Mutex::new(HashMap::new()),
+            started_at: Instant::now(),
+            startup_grace_period: Duration::ZERO,
+            status_holder: StatusHolder::new(initial_check_results, None),
+            latest_results: Mutex::new(None),
         }
     }
 
@@ -224,7 +632,7 @@ mod tests {
         let manager = make_manager(vec![Box::new(SuccessChecker), Box::new(SuccessChecker)]);
         manager.execute_status_checks().await;
         let status = manager.status_holder().current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::OK);
+        assert_eq!(status.state, ServiceState::Up);
         assert!(status.failing_checks.is_empty());
     }
 
@@ -233,7 +641,7 @@ mod tests {
         let manager = make_manager(vec![Box::new(SuccessChecker), Box::new(FailureChecker)]);
         manager.execute_status_checks().await;
         let status = manager.status_holder().current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status.state, ServiceState::Down);
         assert_eq!(status.failing_checks.len(), 1);
         assert_eq!(status.failing_checks[0].check_name, "failure_checker");
     }
@@ -243,16 +651,213 @@ mod tests {
         let manager = make_manager(vec![Box::new(FailureChecker), Box::new(FailureChecker)]);
         manager.execute_status_checks().await;
         let status = manager.status_holder().current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status.state, ServiceState::Down);
+        assert_eq!(status.failing_checks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn warnings_are_reported_as_degraded() {
+        let manager = make_manager(vec![Box::new(SuccessChecker), Box::new(WarningChecker)]);
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Degraded);
+        assert_eq!(status.severity, CheckSeverity::Warning);
+        assert_eq!(status.failing_checks.len(), 1);
+        assert_eq!(status.failing_checks[0].severity, CheckSeverity::Warning);
+    }
+
+    fn api_group(policy: GroupPolicy) -> CheckGroup {
+        CheckGroup {
+            name: "api".to_string(),
+            check_ids: vec!["success".to_string(), "failure".to_string()],
+            policy,
+        }
+    }
+
+    #[tokio::test]
+    async fn group_with_met_policy_reports_members_as_warnings() {
+        let mut manager = make_manager(vec![Box::new(SuccessChecker), Box::new(FailureChecker)]);
+        manager.check_groups = vec![api_group(GroupPolicy::Any)];
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Degraded);
+        assert_eq!(status.failing_checks.len(), 1);
+        assert_eq!(status.failing_checks[0].check_name, "failure_checker");
+        assert_eq!(status.failing_checks[0].severity, CheckSeverity::Warning);
+        assert!(status.failing_checks[0]
+            .failure_reason
+            .starts_with("group api has 1 of 2 checks passing"));
+    }
+
+    #[tokio::test]
+    async fn group_with_missed_policy_returns_503() {
+        let mut manager = make_manager(vec![Box::new(SuccessChecker), Box::new(FailureChecker)]);
+        manager.check_groups = vec![api_group(GroupPolicy::All)];
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Down);
+        assert_eq!(status.failing_checks.len(), 1);
+        assert_eq!(status.failing_checks[0].severity, CheckSeverity::Critical);
+    }
+
+    #[tokio::test]
+    async fn critical_and_warning_returns_503() {
+        let manager = make_manager(vec![Box::new(WarningChecker), Box::new(FailureChecker)]);
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Down);
+        assert_eq!(status.severity, CheckSeverity::Critical);
         assert_eq!(status.failing_checks.len(), 2);
     }
 
+    #[tokio::test]
+    async fn severity_override_downgrades_failures() {
+        let mut manager = make_manager(vec![Box::new(FailureChecker), Box::new(ErrorChecker)]);
+        let settings = CheckSettings {
+            max_severity: CheckSeverity::Warning,
+            ..CheckSettings::default()
+        };
+        for check_id in ["failure", "error"] {
+            manager
+                .check_settings
+                .insert(check_id.to_string(), settings.clone());
+        }
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Degraded);
+        assert_eq!(status.severity, CheckSeverity::Warning);
+        assert_eq!(status.failing_checks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn drain_setting_reports_failures_as_drain() {
+        let mut manager = make_manager(vec![Box::new(FailureChecker), Box::new(ErrorChecker)]);
+        let settings = CheckSettings {
+            drain: true,
+            ..CheckSettings::default()
+        };
+        manager
+            .check_settings
+            .insert("failure".to_string(), settings.clone());
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Down);
+
+        manager.check_settings.insert("error".to_string(), settings);
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Drain);
+        assert_eq!(status.failing_checks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn check_with_failed_dependency_is_skipped() {
+        // the scripted checker has no results and would panic if executed
+        let mut manager = make_manager(vec![
+            Box::new(ScriptedChecker {
+                results: Mutex::new(vec![]),
+            }),
+            Box::new(FailureChecker),
+        ]);
+        let settings = CheckSettings {
+            depends_on: vec!["failure".to_string()],
+            ..CheckSettings::default()
+        };
+        manager
+            .check_settings
+            .insert("scripted".to_string(), settings);
+        manager.execution_waves =
+            execution_waves(&manager.status_checker, &manager.check_settings).unwrap();
+        manager.execute_status_checks().await;
+
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.state, ServiceState::Down);
+        assert_eq!(status.failing_checks.len(), 2);
+        assert_eq!(status.failing_checks[0].check_name, "scripted_checker");
+        assert_eq!(
+            status.failing_checks[0].failure_reason,
+            "skipped: dependency failure failed"
+        );
+        assert_eq!(status.failing_checks[1].check_name, "failure_checker");
+    }
+
+    #[tokio::test]
+    async fn check_with_passing_dependency_is_executed() {
+        let mut manager = make_manager(vec![Box::new(FailureChecker), Box::new(SuccessChecker)]);
+        let settings = CheckSettings {
+            depends_on: vec!["success".to_string()],
+            ..CheckSettings::default()
+        };
+        manager
+            .check_settings
+            .insert("failure".to_string(), settings);
+        manager.execution_waves =
+            execution_waves(&manager.status_checker, &manager.check_settings).unwrap();
+        manager.execute_status_checks().await;
+
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.failing_checks.len(), 1);
+        assert_eq!(status.failing_checks[0].failure_reason, "always fails");
+    }
+
+    #[tokio::test]
+    async fn rise_and_fall_dampen_status_changes() {
+        let mut manager = make_manager(vec![Box::new(ScriptedChecker {
+            results: Mutex::new(vec![true, false, false, true, true]),
+        })]);
+        let settings = CheckSettings {
+            rise: 2,
+            fall: 2,
+            ..CheckSettings::default()
+        };
+        manager
+            .check_settings
+            .insert("scripted".to_string(), settings);
+
+        let mut states = vec![];
+        let mut streaks = vec![];
+        let mut check_streaks = vec![];
+        for _ in 0..5 {
+            manager.execute_status_checks().await;
+            let status = manager.status_holder().current_status().await;
+            states.push(status.state);
+            streaks.push(status.failing_checks.first().and_then(|check| check.streak));
+            check_streaks.push(status.checks[0].streak);
+        }
+        assert_eq!(
+            states,
+            vec![
+                ServiceState::Up,
+                ServiceState::Up,
+                ServiceState::Down,
+                ServiceState::Down,
+                ServiceState::Up
+            ]
+        );
+        assert_eq!(
+            streaks[3],
+            Some(CheckStreak {
+                failures: 0,
+                successes: 1
+            })
+        );
+        // the streak of a failure that is still dampened is listed with the check
+        assert_eq!(streaks[1], None);
+        assert_eq!(
+            check_streaks[1],
+            Some(CheckStreak {
+                failures: 1,
+                successes: 0
+            })
+        );
+    }
+
     #[tokio::test]
     async fn error_treated_as_failure() {
         let manager = make_manager(vec![Box::new(ErrorChecker)]);
         manager.execute_status_checks().await;
         let status = manager.status_holder().current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status.state, ServiceState::Down);
         assert_eq!(status.failing_checks.len(), 1);
         assert!(status.failing_checks[0]
             .failure_reason
@@ -268,7 +873,7 @@ mod tests {
         ]);
         manager.execute_status_checks().await;
         let status = manager.status_holder().current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::OK);
+        assert_eq!(status.state, ServiceState::Up);
         assert!(status.failing_checks.is_empty());
     }
 
@@ -277,16 +882,107 @@ mod tests {
         let manager = make_manager(vec![]);
         manager.execute_status_checks().await;
         let status = manager.status_holder().current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::OK);
+        assert_eq!(status.state, ServiceState::Up);
         assert!(status.failing_checks.is_empty());
     }
 
     #[tokio::test]
-    async fn initial_state_is_503() {
+    async fn initial_state_is_503_with_pending_checks() {
         let manager = make_manager(vec![Box::new(SuccessChecker)]);
         let status = manager.status_holder().current_status().await;
-        assert_eq!(status.api_response_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status.state, ServiceState::Down);
         assert_eq!(status.failing_checks.len(), 1);
-        assert_eq!(status.failing_checks[0].check_name, "Initial Check");
+        assert_eq!(status.failing_checks[0].check_name, "success_checker");
+        assert_eq!(
+            status.failing_checks[0].failure_reason,
+            PENDING_FAILURE_REASON
+        );
+    }
+
+    #[test]
+    fn initial_failure_depends_on_initial_state() {
+        let checker: Box<dyn StatusChecker> = Box::new(FailureChecker);
+        let settings = |initial_state| CheckSettings {
+            initial_state,
+            ..CheckSettings::default()
+        };
+
+        let failure =
+            StatusManager::initial_failure(&checker, &settings(CheckInitialState::Up), None);
+        assert!(failure.is_none());
+        let failure =
+            StatusManager::initial_failure(&checker, &settings(CheckInitialState::Down), None);
+        assert_eq!(failure.unwrap().failure_reason, "initial state is down");
+        let failure =
+            StatusManager::initial_failure(&checker, &settings(CheckInitialState::Pending), None);
+        assert_eq!(failure.unwrap().failure_reason, PENDING_FAILURE_REASON);
+    }
+
+    #[tokio::test]
+    async fn grace_period_uses_initial_state_until_first_success() {
+        let mut manager = make_manager(vec![
+            Box::new(ScriptedChecker {
+                results: Mutex::new(vec![false, true, false]),
+            }),
+            Box::new(FailureChecker),
+        ]);
+        manager.startup_grace_period = Duration::from_secs(60);
+        let settings = CheckSettings {
+            initial_state: CheckInitialState::Up,
+            ..CheckSettings::default()
+        };
+        manager
+            .check_settings
+            .insert("scripted".to_string(), settings);
+
+        // the scripted check is assumed up, the failure check is pending until it succeeds
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.failing_checks.len(), 1);
+        assert_eq!(status.failing_checks[0].check_name, "failure_checker");
+        assert_eq!(
+            status.failing_checks[0].failure_reason,
+            PENDING_FAILURE_REASON
+        );
+
+        // after the first success, failures are reported even during the grace period
+        manager.execute_status_checks().await;
+        manager.execute_status_checks().await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.failing_checks.len(), 2);
+        assert_eq!(status.failing_checks[0].check_name, "scripted_checker");
+    }
+
+    #[tokio::test]
+    async fn changed_paths_only_re_execute_watching_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.disabled");
+        let manager = make_manager(vec![
+            // the scripted check would panic if it were executed a second time
+            Box::new(ScriptedChecker {
+                results: Mutex::new(vec![false]),
+            }),
+            Box::new(FileWatchingChecker {
+                file_path: file_path.clone(),
+            }),
+        ]);
+        manager.execute_status_checks().await;
+        let timestamp = manager.status_holder().current_status().await.timestamp;
+
+        std::fs::write(&file_path, "").unwrap();
+        manager
+            .execute_checks_watching(&[WatchedPath::Directory(dir.path().to_path_buf())])
+            .await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.failing_checks.len(), 1);
+
+        manager
+            .execute_checks_watching(&[WatchedPath::File(file_path)])
+            .await;
+        let status = manager.status_holder().current_status().await;
+        assert_eq!(status.timestamp, timestamp);
+        assert_eq!(status.failing_checks.len(), 2);
+        assert_eq!(status.failing_checks[0].check_name, "scripted_checker");
+        assert_eq!(status.failing_checks[1].check_name, "file_watching_checker");
     }
 }
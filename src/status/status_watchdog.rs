@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use tokio::time;
+
+use crate::status::status_holder::StatusHolder;
+
+/// Periodically verifies that check cycles complete in time, and logs a warning
+/// while the current check results are older than expected. The results of a cycle
+/// are at most one revalidation interval plus the duration of the next cycle old,
+/// therefore cycles taking longer than the revalidation interval are reported.
+pub(crate) async fn watch_check_cycles(
+    status_holder: StatusHolder,
+    revalidation_interval: Duration,
+) {
+    let max_expected_age = max_expected_result_age(revalidation_interval);
+    loop {
+        time::sleep(revalidation_interval).await;
+        let result_age = status_holder.current_status().await.timestamp.elapsed();
+        if result_age > max_expected_age {
+            log::warn!(
+                "check cycle is overrunning: check results are {}s old, revalidation interval is {}s",
+                result_age.as_secs(),
+                revalidation_interval.as_secs()
+            );
+        }
+    }
+}
+
+/// Returns the maximum age check results are expected to have, as long as
+/// check cycles do not take longer than the given revalidation interval.
+fn max_expected_result_age(revalidation_interval: Duration) -> Duration {
+    revalidation_interval * 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_result_age_covers_interval_and_cycle() {
+        assert_eq!(
+            max_expected_result_age(Duration::from_secs(5)),
+            Duration::from_secs(10)
+        );
+    }
+}
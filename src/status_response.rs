@@ -0,0 +1,278 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::options::Options;
+use crate::status::status_holder::{CheckStatus, FailingCheck, ServiceState, StatusCheckResults};
+
+/// The format of the status response bodies.
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BodyFormat {
+    /// The failing checks as json array.
+    Json,
+    /// The state as plain text, followed by a line per failing check.
+    Text,
+    /// The configured body template with replaced placeholders.
+    Template,
+}
+
+/// An additional header sent with every status response, parsed from `<name>: <value>`.
+#[derive(Clone, Debug)]
+pub(crate) struct ResponseHeader {
+    pub name: HeaderName,
+    pub value: HeaderValue,
+}
+
+impl FromStr for ResponseHeader {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, header_value) = value
+            .split_once(':')
+            .ok_or_else(|| format!("{} must have the format <name>: <value>", value))?;
+        let name = HeaderName::from_str(name.trim())
+            .map_err(|error| format!("invalid header name {}: {}", name, error))?;
+        let header_value = HeaderValue::from_str(header_value.trim())
+            .map_err(|error| format!("invalid value for header {}: {}", name, error))?;
+        Ok(Self {
+            name,
+            value: header_value,
+        })
+    }
+}
+
+/// The response body in verbose mode, listing the passing checks as well.
+#[derive(Serialize)]
+struct VerboseStatus {
+    failing_checks: Vec<FailingCheck>,
+    checks: Vec<CheckStatus>,
+}
+
+/// The settings how the status of the service is answered by the status endpoints.
+#[derive(Clone, Debug)]
+pub(crate) struct StatusResponseSettings {
+    /// The status code sent while all checks pass.
+    up_status_code: StatusCode,
+    /// The status code sent while checks only fail with a warning.
+    degraded_status_code: StatusCode,
+    /// The status code sent while a maintenance hold is present.
+    maintenance_status_code: StatusCode,
+    /// The status code sent while a drain hold is present.
+    drain_status_code: StatusCode,
+    /// The status code sent while a check fails critically.
+    down_status_code: StatusCode,
+    /// The format of the response bodies.
+    body_format: BodyFormat,
+    /// The template of the response bodies, if the template format is used.
+    body_template: String,
+    /// The additional headers sent with every response.
+    extra_headers: Vec<ResponseHeader>,
+    /// The additional headers sent while the service is draining.
+    drain_headers: Vec<ResponseHeader>,
+}
+
+impl StatusResponseSettings {
+    /// Constructs the response settings from the given options. Fails if
+    /// the template body format is used without a body template.
+    pub fn from_options(options: &Options) -> anyhow::Result<Self> {
+        let body_template = match (options.body_format, &options.body_template) {
+            (BodyFormat::Template, None) => {
+                bail!("the template body format requires a body template")
+            }
+            (_, body_template) => body_template.clone().unwrap_or_default(),
+        };
+        Ok(Self {
+            up_status_code: options.up_status_code,
+            degraded_status_code: options.degraded_status_code,
+            maintenance_status_code: options.maintenance_status_code,
+            drain_status_code: options.drain_status_code,
+            down_status_code: options.down_status_code,
+            body_format: options.body_format,
+            body_template,
+            extra_headers: options.response_headers.clone().unwrap_or_default(),
+            drain_headers: options.drain_response_headers.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Returns the status code to send for the given state of the service.
+    pub fn status_code(&self, state: ServiceState) -> StatusCode {
+        match state {
+            ServiceState::Up => self.up_status_code,
+            ServiceState::Degraded => self.degraded_status_code,
+            ServiceState::Drain => self.drain_status_code,
+            ServiceState::Maintenance => self.maintenance_status_code,
+            ServiceState::Down => self.down_status_code,
+        }
+    }
+
+    /// Returns the additional headers sent with responses for the given state of the
+    /// service. The drain headers are only sent while the service is draining.
+    pub fn extra_headers(&self, state: ServiceState) -> Vec<(HeaderName, HeaderValue)> {
+        let drain_headers = match state {
+            ServiceState::Drain => self.drain_headers.as_slice(),
+            _ => &[],
+        };
+        self.extra_headers
+            .iter()
+            .chain(drain_headers)
+            .map(|header| (header.name.clone(), header.value.clone()))
+            .collect()
+    }
+
+    /// Renders the response body for the given status in the configured format.
+    /// In verbose mode the passing checks are listed as well.
+    pub fn render_body(
+        &self,
+        status: StatusCheckResults,
+        status_code: StatusCode,
+        verbose: bool,
+    ) -> Response {
+        match self.body_format {
+            BodyFormat::Json if verbose => Json(VerboseStatus {
+                failing_checks: status.failing_checks,
+                checks: status.checks,
+            })
+            .into_response(),
+            BodyFormat::Json => Json(status.failing_checks).into_response(),
+            BodyFormat::Text => Self::plain_text(render_text(&status, verbose)),
+            BodyFormat::Template => {
+                Self::plain_text(render_template(&self.body_template, &status, status_code))
+            }
+        }
+    }
+
+    fn plain_text(body: String) -> Response {
+        ([(CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response()
+    }
+}
+
+/// Renders the given status as plain text. The first line contains `OK` if the
+/// service is up or the upper case state otherwise, followed by a line per failing
+/// check. In verbose mode a line per selected check is listed instead.
+fn render_text(status: &StatusCheckResults, verbose: bool) -> String {
+    let mut lines = vec![match status.state {
+        ServiceState::Up => String::from("OK"),
+        state => state.to_string().to_uppercase(),
+    }];
+    if verbose {
+        lines.extend(status.checks.iter().map(|check| {
+            format!(
+                "{}: {}",
+                check.check_name,
+                check.failure_reason.as_deref().unwrap_or("ok")
+            )
+        }));
+    } else {
+        lines.extend(status.failing_checks.iter().map(|failing_check| {
+            format!(
+                "{}: {}",
+                failing_check.check_name, failing_check.failure_reason
+            )
+        }));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Renders the given body template, replacing the placeholders `{state}`, `{status_code}`,
+/// `{severity}`, `{age}`, `{failing_check_count}` and `{failing_checks}` with the values
+/// of the given status.
+fn render_template(template: &str, status: &StatusCheckResults, status_code: StatusCode) -> String {
+    let failing_checks: Vec<String> = status
+        .failing_checks
+        .iter()
+        .map(|failing_check| {
+            format!(
+                "{}: {}",
+                failing_check.check_name, failing_check.failure_reason
+            )
+        })
+        .collect();
+    template
+        .replace("{state}", &status.state.to_string())
+        .replace("{status_code}", status_code.as_str())
+        .replace("{severity}", &status.severity.to_string())
+        .replace("{age}", &status.timestamp.elapsed().as_secs().to_string())
+        .replace(
+            "{failing_check_count}",
+            &status.failing_checks.len().to_string(),
+        )
+        .replace("{failing_checks}", &failing_checks.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::status_checker::CheckSeverity;
+
+    fn failing_status() -> StatusCheckResults {
+        StatusCheckResults::from_failing_checks(vec![FailingCheck {
+            check_name: "disk space check".to_string(),
+            failure_reason: "disk full".to_string(),
+            severity: CheckSeverity::Critical,
+            perfdata: vec![],
+            streak: None,
+            maintenance: false,
+            drain: false,
+            holds: vec![],
+        }])
+    }
+
+    #[test]
+    fn parses_response_header() {
+        let header: ResponseHeader = "Cache-Control: no-store".parse().unwrap();
+        assert_eq!(header.name, "cache-control");
+        assert_eq!(header.value, "no-store");
+        assert!("Cache-Control".parse::<ResponseHeader>().is_err());
+        assert!("Cache Control: no-store".parse::<ResponseHeader>().is_err());
+    }
+
+    #[test]
+    fn renders_text_body() {
+        let status = StatusCheckResults::from_failing_checks(vec![]);
+        assert_eq!(render_text(&status, false), "OK\n");
+        assert_eq!(
+            render_text(&failing_status(), false),
+            "DOWN\ndisk space check: disk full\n"
+        );
+    }
+
+    #[test]
+    fn sends_drain_headers_while_draining_only() {
+        let settings = StatusResponseSettings {
+            up_status_code: StatusCode::OK,
+            degraded_status_code: StatusCode::OK,
+            maintenance_status_code: StatusCode::SERVICE_UNAVAILABLE,
+            drain_status_code: StatusCode::NOT_FOUND,
+            down_status_code: StatusCode::SERVICE_UNAVAILABLE,
+            body_format: BodyFormat::Json,
+            body_template: String::new(),
+            extra_headers: vec!["Cache-Control: no-store".parse().unwrap()],
+            drain_headers: vec!["Connection: close".parse().unwrap()],
+        };
+        assert_eq!(
+            settings.status_code(ServiceState::Drain),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(settings.extra_headers(ServiceState::Up).len(), 1);
+        let headers = settings.extra_headers(ServiceState::Drain);
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[1].0, "connection");
+        assert_eq!(headers[1].1, "close");
+    }
+
+    #[test]
+    fn renders_body_template() {
+        let body = render_template(
+            "{state} {status_code} {severity} {failing_check_count} [{failing_checks}]",
+            &failing_status(),
+            StatusCode::SERVICE_UNAVAILABLE,
+        );
+        assert_eq!(body, "down 503 critical 1 [disk space check: disk full]");
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::time::{timeout_at, Instant};
+
+/// The time without further changes after which a burst of changes is reported,
+/// as editors and tools like rsync change files through multiple events.
+const SETTLE_DELAY: Duration = Duration::from_millis(100);
+
+/// The maximum time a burst of changes is coalesced before it is reported.
+const MAX_COALESCE_DELAY: Duration = Duration::from_secs(1);
+
+/// A path whose changes are watched.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum WatchedPath {
+    /// A single file, which is watched for being created, changed or removed.
+    File(PathBuf),
+    /// A directory, in which any file is watched for being created, changed or removed.
+    Directory(PathBuf),
+}
+
+impl WatchedPath {
+    /// Returns the directory to watch and the name of the file in the directory
+    /// to filter the changes by, if only a single file is watched.
+    fn watch_target(&self) -> (PathBuf, Option<OsString>) {
+        match self {
+            WatchedPath::File(file_path) => {
+                // files are watched through their parent directory, as they
+                // might not exist yet or be replaced by renaming
+                let dir_path = match file_path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                    _ => PathBuf::from("."),
+                };
+                (
+                    dir_path,
+                    file_path.file_name().map(|name| name.to_os_string()),
+                )
+            }
+            WatchedPath::Directory(dir_path) => (dir_path.clone(), None),
+        }
+    }
+}
+
+/// An event read from inotify.
+#[derive(Debug, PartialEq)]
+struct InotifyEvent {
+    /// The descriptor of the watch the event belongs to.
+    watch_descriptor: i32,
+    /// The name of the changed file in the watched directory, if any.
+    file_name: Option<OsString>,
+    /// Whether events were lost, as the event queue overflowed.
+    queue_overflow: bool,
+    /// Whether the watch was removed, for example as the directory was removed.
+    watch_removed: bool,
+}
+
+/// Watches paths for changes through inotify.
+#[derive(Debug)]
+pub(crate) struct FileWatcher {
+    inotify: inotify::Inotify,
+    /// The watched paths with the file names to filter the changes by, per watched
+    /// directory. A missing file name filter means that any change is reported.
+    file_filters: HashMap<i32, Vec<(Option<OsString>, WatchedPath)>>,
+    /// The paths that are currently not watched, as their directory does not exist
+    /// (anymore). Watching them is retried by `rewatch`.
+    unwatched_paths: Vec<WatchedPath>,
+}
+
+impl FileWatcher {
+    /// Sets up the watches for the given paths. Paths that cannot be watched, for example
+    /// because their directory does not exist, are skipped. Fails if file watches are not
+    /// supported or no path can be watched.
+    pub fn new(watched_paths: &[WatchedPath]) -> io::Result<Self> {
+        let mut file_watcher = Self {
+            inotify: inotify::Inotify::new()?,
+            file_filters: HashMap::new(),
+            unwatched_paths: vec![],
+        };
+        for watched_path in watched_paths {
+            if let Err(error) = file_watcher.add_watch(watched_path) {
+                log::warn!(
+                    "unable to watch {:?}, changes are only detected by the check cycles: {}",
+                    watched_path,
+                    error
+                );
+                file_watcher.unwatched_paths.push(watched_path.clone());
+            }
+        }
+        if file_watcher.file_filters.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "none of the paths can be watched",
+            ));
+        }
+        Ok(file_watcher)
+    }
+
+    /// Retries to watch the paths that are currently not watched.
+    pub fn rewatch(&mut self) {
+        for watched_path in std::mem::take(&mut self.unwatched_paths) {
+            match self.add_watch(&watched_path) {
+                Ok(()) => log::info!("watching {:?} again", watched_path),
+                Err(error) => {
+                    log::debug!("unable to watch {:?}: {}", watched_path, error);
+                    self.unwatched_paths.push(watched_path);
+                }
+            }
+        }
+    }
+
+    fn add_watch(&mut self, watched_path: &WatchedPath) -> io::Result<()> {
+        let (dir_path, file_name) = watched_path.watch_target();
+        let watch_descriptor = self.inotify.add_watch(&dir_path)?;
+        self.file_filters
+            .entry(watch_descriptor)
+            .or_default()
+            .push((file_name, watched_path.clone()));
+        Ok(())
+    }
+
+    /// Waits until some of the watched paths changed, and returns the changed paths.
+    /// Changes following each other in quick succession are reported at once.
+    pub async fn changed(&mut self) -> io::Result<Vec<WatchedPath>> {
+        let mut changed_paths = self.next_changes().await?;
+        let coalesce_deadline = Instant::now() + MAX_COALESCE_DELAY;
+        loop {
+            let settle_deadline = (Instant::now() + SETTLE_DELAY).min(coalesce_deadline);
+            match timeout_at(settle_deadline, self.next_changes()).await {
+                Ok(next_changed_paths) => {
+                    for changed_path in next_changed_paths? {
+                        if !changed_paths.contains(&changed_path) {
+                            changed_paths.push(changed_path);
+                        }
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        log::debug!("watched paths changed: {:?}", changed_paths);
+        Ok(changed_paths)
+    }
+
+    /// Waits for the next events that change one of the watched paths.
+    async fn next_changes(&mut self) -> io::Result<Vec<WatchedPath>> {
+        loop {
+            let events = self.inotify.read_events().await?;
+            let changed_paths = self.changed_paths_of(&events);
+            if !changed_paths.is_empty() {
+                return Ok(changed_paths);
+            }
+        }
+    }
+
+    /// Returns the watched paths changed by the given events. All watched paths are
+    /// assumed as changed if events were lost. Paths whose watch was removed are
+    /// reported as changed and watched again by the next `rewatch`.
+    fn changed_paths_of(&mut self, events: &[InotifyEvent]) -> Vec<WatchedPath> {
+        let mut changed_paths: Vec<WatchedPath> = vec![];
+        let mut add_changed_path = |watched_path: &WatchedPath| {
+            if !changed_paths.contains(watched_path) {
+                changed_paths.push(watched_path.clone());
+            }
+        };
+        for event in events {
+            if event.queue_overflow {
+                log::warn!("file change events were lost, assuming all watched paths changed");
+                self.file_filters
+                    .values()
+                    .flatten()
+                    .for_each(|(_, watched_path)| add_changed_path(watched_path));
+                continue;
+            }
+
+            let matching_paths = self
+                .file_filters
+                .get(&event.watch_descriptor)
+                .into_iter()
+                .flatten()
+                .filter(|(filter, _)| match (filter, &event.file_name) {
+                    (None, _) => true,
+                    (Some(filter), Some(file_name)) => filter == file_name,
+                    (Some(_), None) => false,
+                });
+            for (_, watched_path) in matching_paths {
+                add_changed_path(watched_path);
+            }
+
+            if event.watch_removed {
+                let removed_filters = self
+                    .file_filters
+                    .remove(&event.watch_descriptor)
+                    .unwrap_or_default();
+                for (_, watched_path) in removed_filters {
+                    log::warn!(
+                        "watch of {:?} was removed, trying to watch it again after the next check cycle",
+                        watched_path
+                    );
+                    add_changed_path(&watched_path);
+                    self.unwatched_paths.push(watched_path);
+                }
+            }
+        }
+        changed_paths
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use tokio::io::unix::AsyncFd;
+
+    use super::InotifyEvent;
+
+    /// The events of the watched directories that indicate a changed file.
+    const WATCH_MASK: u32 = libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_CLOSE_WRITE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVE_SELF;
+
+    /// A non-blocking inotify instance, registered with the tokio runtime.
+    #[derive(Debug)]
+    pub(super) struct Inotify {
+        fd: AsyncFd<OwnedFd>,
+    }
+
+    impl Inotify {
+        pub fn new() -> io::Result<Self> {
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            Ok(Self {
+                fd: AsyncFd::new(fd)?,
+            })
+        }
+
+        /// Watches the given directory, returns the descriptor of the watch.
+        pub fn add_watch(&self, dir_path: &Path) -> io::Result<i32> {
+            let c_path = CString::new(dir_path.as_os_str().as_bytes())
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+            let watch_descriptor = unsafe {
+                libc::inotify_add_watch(self.fd.as_raw_fd(), c_path.as_ptr(), WATCH_MASK)
+            };
+            if watch_descriptor < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(watch_descriptor)
+        }
+
+        /// Waits for the next events.
+        pub async fn read_events(&self) -> io::Result<Vec<InotifyEvent>> {
+            let mut buffer = [0u8; 4096];
+            let read_bytes = loop {
+                let mut guard = self.fd.readable().await?;
+                let result = guard.try_io(|fd| {
+                    let read_bytes = unsafe {
+                        libc::read(
+                            fd.as_raw_fd(),
+                            buffer.as_mut_ptr() as *mut libc::c_void,
+                            buffer.len(),
+                        )
+                    };
+                    if read_bytes < 0 {
+                        Err(io::Error::last_os_error())
+                    } else {
+                        Ok(read_bytes as usize)
+                    }
+                });
+                if let Ok(result) = result {
+                    break result?;
+                }
+            };
+
+            let header_size = std::mem::size_of::<libc::inotify_event>();
+            let mut events = vec![];
+            let mut offset = 0;
+            while offset + header_size <= read_bytes {
+                let event = unsafe {
+                    std::ptr::read_unaligned(buffer[offset..].as_ptr() as *const libc::inotify_event)
+                };
+                let name_start = offset + header_size;
+                let name_end = (name_start + event.len as usize).min(read_bytes);
+                let name = &buffer[name_start..name_end];
+                // the name is padded with null bytes
+                let name = &name[..name
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .unwrap_or(name.len())];
+                let file_name = Some(name)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| OsStr::from_bytes(name).to_os_string());
+                events.push(InotifyEvent {
+                    watch_descriptor: event.wd,
+                    file_name,
+                    queue_overflow: event.mask & libc::IN_Q_OVERFLOW != 0,
+                    watch_removed: event.mask & libc::IN_IGNORED != 0,
+                });
+                offset = name_end;
+            }
+            Ok(events)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod inotify {
+    use std::io;
+    use std::path::Path;
+
+    use super::InotifyEvent;
+
+    /// Placeholder on platforms without inotify, which cannot set up watches.
+    #[derive(Debug)]
+    pub(super) struct Inotify;
+
+    impl Inotify {
+        pub fn new() -> io::Result<Self> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "file watches are only supported on linux",
+            ))
+        }
+
+        pub fn add_watch(&self, _: &Path) -> io::Result<i32> {
+            unreachable!()
+        }
+
+        pub async fn read_events(&self) -> io::Result<Vec<InotifyEvent>> {
+            unreachable!()
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn changed_within(file_watcher: &mut FileWatcher, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, file_watcher.changed())
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn reports_bursts_of_changes_at_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.disabled");
+        let drop_in_path = dir.path().join("mtc.d");
+        std::fs::create_dir(&drop_in_path).unwrap();
+        let watched_paths = [
+            WatchedPath::File(file_path.clone()),
+            WatchedPath::Directory(drop_in_path.clone()),
+        ];
+        let mut file_watcher = FileWatcher::new(&watched_paths).unwrap();
+
+        // an editor writing a temporary file and renaming it over the original
+        std::fs::write(dir.path().join(".easycheck.disabled.swp"), "").unwrap();
+        std::fs::rename(dir.path().join(".easycheck.disabled.swp"), &file_path).unwrap();
+        std::fs::write(&file_path, "reason=deployment").unwrap();
+        std::fs::write(drop_in_path.join("dba"), "").unwrap();
+        let changed_paths = file_watcher.changed().await.unwrap();
+        assert_eq!(changed_paths, watched_paths);
+        assert!(!changed_within(&mut file_watcher, Duration::from_millis(200)).await);
+    }
+
+    #[test]
+    fn files_are_watched_through_their_directory() {
+        let watched_path = WatchedPath::File(PathBuf::from("/var/run/easycheck.disabled"));
+        assert_eq!(
+            watched_path.watch_target(),
+            (
+                PathBuf::from("/var/run"),
+                Some(OsString::from("easycheck.disabled"))
+            )
+        );
+        let watched_path = WatchedPath::File(PathBuf::from("easycheck.disabled"));
+        assert_eq!(watched_path.watch_target().0, PathBuf::from("."));
+    }
+
+    #[tokio::test]
+    async fn reports_changes_of_watched_file_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.disabled");
+        let mut file_watcher = FileWatcher::new(&[WatchedPath::File(file_path.clone())]).unwrap();
+
+        std::fs::write(dir.path().join("other"), "").unwrap();
+        assert!(!changed_within(&mut file_watcher, Duration::from_millis(200)).await);
+
+        std::fs::write(&file_path, "").unwrap();
+        assert!(changed_within(&mut file_watcher, Duration::from_secs(2)).await);
+    }
+
+    #[tokio::test]
+    async fn reports_changes_in_watched_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file_watcher =
+            FileWatcher::new(&[WatchedPath::Directory(dir.path().to_path_buf())]).unwrap();
+
+        std::fs::write(dir.path().join("dba"), "").unwrap();
+        assert!(changed_within(&mut file_watcher, Duration::from_secs(2)).await);
+    }
+
+    #[tokio::test]
+    async fn queue_overflow_reports_all_watched_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let watched_paths = [
+            WatchedPath::File(dir.path().join("easycheck.disabled")),
+            WatchedPath::Directory(dir.path().to_path_buf()),
+        ];
+        let mut file_watcher = FileWatcher::new(&watched_paths).unwrap();
+        let overflow = InotifyEvent {
+            watch_descriptor: -1,
+            file_name: None,
+            queue_overflow: true,
+            watch_removed: false,
+        };
+        assert_eq!(file_watcher.changed_paths_of(&[overflow]), watched_paths);
+    }
+
+    #[tokio::test]
+    async fn removed_directory_is_watched_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let drop_in_path = dir.path().join("mtc.d");
+        std::fs::create_dir(&drop_in_path).unwrap();
+        let watched_path = WatchedPath::Directory(drop_in_path.clone());
+        let mut file_watcher = FileWatcher::new(std::slice::from_ref(&watched_path)).unwrap();
+
+        std::fs::remove_dir(&drop_in_path).unwrap();
+        let changed_paths = tokio::time::timeout(Duration::from_secs(2), file_watcher.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed_paths, vec![watched_path.clone()]);
+        assert_eq!(file_watcher.unwatched_paths, vec![watched_path.clone()]);
+
+        // the directory cannot be watched until it is created again
+        file_watcher.rewatch();
+        assert_eq!(file_watcher.unwatched_paths, vec![watched_path]);
+        std::fs::create_dir(&drop_in_path).unwrap();
+        file_watcher.rewatch();
+        assert!(file_watcher.unwatched_paths.is_empty());
+
+        std::fs::write(drop_in_path.join("dba"), "").unwrap();
+        assert!(changed_within(&mut file_watcher, Duration::from_secs(2)).await);
+    }
+
+    #[tokio::test]
+    async fn fails_without_watchable_path() {
+        let watched_path = WatchedPath::Directory(PathBuf::from("/easycheck/nonexistent"));
+        assert!(FileWatcher::new(&[watched_path]).is_err());
+    }
+}
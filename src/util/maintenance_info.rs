@@ -0,0 +1,289 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+/// The optional details of a maintenance hold, read from the contents of a
+/// maintenance file. The contents are either a json object or `key=value` lines,
+/// an empty file holds no details.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MaintenanceInfo {
+    /// The reason of the maintenance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The author of the maintenance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+    /// The time the maintenance started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// The rfc 3339 timestamp the maintenance ends at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+impl MaintenanceInfo {
+    /// Parses the given maintenance file contents. Contents starting with `{` are
+    /// parsed as json object, all other contents as `key=value` lines. Empty lines,
+    /// lines starting with `#` and unknown keys are ignored.
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let content = content.trim();
+        if content.starts_with('{') {
+            return serde_json::from_str(content).context("invalid json maintenance details");
+        }
+
+        let mut info = Self::default();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                log::debug!("ignoring maintenance detail line without value: {}", line);
+                continue;
+            };
+            let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+            match key.trim() {
+                "reason" => info.reason = value,
+                "by" => info.by = value,
+                "since" => info.since = value,
+                "until" => info.until = value,
+                key => log::debug!("ignoring unknown maintenance detail {}", key),
+            }
+        }
+        Ok(info)
+    }
+
+    /// Parses the timestamp the maintenance ends at, if given.
+    pub fn until_timestamp(&self) -> anyhow::Result<Option<Timestamp>> {
+        self.until
+            .as_deref()
+            .map(|until| {
+                until
+                    .parse::<Timestamp>()
+                    .with_context(|| format!("invalid maintenance end {}", until))
+            })
+            .transpose()
+    }
+
+    /// Checks if the maintenance ended already. Invalid end timestamps are ignored.
+    pub fn has_ended(&self) -> bool {
+        matches!(self.until_timestamp(), Ok(Some(until)) if until <= Timestamp::now())
+    }
+
+    /// Describes the maintenance for the failure reason of a check, e.g.
+    /// `deployment (by jane, until 2024-01-01T12:00:00Z)`. Returns `None`
+    /// if no details are given.
+    pub fn describe(&self) -> Option<String> {
+        let details: Vec<String> = [
+            ("by", &self.by),
+            ("since", &self.since),
+            ("until", &self.until),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_ref().map(|value| format!("{} {}", name, value)))
+        .collect();
+        match (&self.reason, details.is_empty()) {
+            (None, true) => None,
+            (Some(reason), true) => Some(reason.clone()),
+            (None, false) => Some(format!("({})", details.join(", "))),
+            (Some(reason), false) => Some(format!("{} ({})", reason, details.join(", "))),
+        }
+    }
+}
+
+/// The maintenance details read from an existing maintenance file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct MaintenanceFile {
+    /// The details of the maintenance.
+    pub info: MaintenanceInfo,
+    /// Describes why the contents of the file were (partially) ignored, if they are invalid.
+    pub problem: Option<String>,
+}
+
+/// Reads the maintenance details of the given file, along with the problems of its
+/// contents. Returns None if the file does not exist. Contents that cannot be parsed
+/// are treated as no details.
+pub(crate) async fn inspect_maintenance_file(
+    file_path: &Path,
+) -> std::io::Result<Option<MaintenanceFile>> {
+    let content = match fs::read(file_path).await {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    let file = match MaintenanceInfo::parse(&String::from_utf8_lossy(&content)) {
+        Ok(info) => match info.until_timestamp() {
+            Ok(_) => MaintenanceFile {
+                info,
+                problem: None,
+            },
+            Err(error) => MaintenanceFile {
+                info,
+                problem: Some(format!("ignoring end: {:#}", error)),
+            },
+        },
+        Err(error) => MaintenanceFile {
+            info: MaintenanceInfo::default(),
+            problem: Some(format!("unable to parse details: {:#}", error)),
+        },
+    };
+    Ok(Some(file))
+}
+
+/// Reads the maintenance details of the given file. Returns None if the file does not
+/// exist, contents that cannot be parsed are treated as no details.
+pub(crate) async fn read_maintenance_file(
+    file_path: &Path,
+) -> std::io::Result<Option<MaintenanceInfo>> {
+    let file = inspect_maintenance_file(file_path).await?;
+    Ok(file.map(|file| {
+        if let Some(problem) = &file.problem {
+            log::debug!("{:?}: {}", file_path, problem);
+        }
+        file.info
+    }))
+}
+
+/// Writes the given maintenance details as json into the given file. The file is
+/// replaced atomically, so that it is never observed with partial contents.
+pub(crate) async fn write_maintenance_file(
+    file_path: &Path,
+    info: &MaintenanceInfo,
+) -> std::io::Result<()> {
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
+    // hidden, so that the file is not picked up from drop-in directories
+    let temp_file_path = file_path.with_file_name(format!(".{}.tmp", file_name));
+    fs::write(&temp_file_path, serde_json::to_vec(info)?).await?;
+    fs::rename(&temp_file_path, file_path).await
+}
+
+/// Lists the files in the given drop-in directory, sorted by name. Hidden files (like
+/// temporary files of editors) and subdirectories are skipped, a missing directory
+/// contains no files.
+pub(crate) async fn list_drop_in_files(dir_path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries = match fs::read_dir(dir_path).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => return Err(error),
+    };
+    let mut files = vec![];
+    while let Some(entry) = entries.next_entry().await? {
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        // follow symlinks, the entry was possibly removed in the meantime
+        let is_file = fs::metadata(entry.path())
+            .await
+            .is_ok_and(|metadata| metadata.is_file());
+        if !hidden && is_file {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_empty_content() {
+        assert_eq!(
+            MaintenanceInfo::parse("").unwrap(),
+            MaintenanceInfo::default()
+        );
+        assert_eq!(MaintenanceInfo::parse(" \n").unwrap().describe(), None);
+    }
+
+    #[test]
+    fn parses_key_value_lines() {
+        let info = MaintenanceInfo::parse(
+            "# kernel update\nreason = kernel update\nby=jane\nticket=OPS-1\nuntil=2024-01-01T12:00:00Z\n",
+        )
+        .unwrap();
+        assert_eq!(info.reason.as_deref(), Some("kernel update"));
+        assert_eq!(info.by.as_deref(), Some("jane"));
+        assert_eq!(info.since, None);
+        assert_eq!(
+            info.until_timestamp().unwrap(),
+            Some("2024-01-01T12:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            info.describe().as_deref(),
+            Some("kernel update (by jane, until 2024-01-01T12:00:00Z)")
+        );
+    }
+
+    #[test]
+    fn parses_json_object() {
+        let info =
+            MaintenanceInfo::parse(r#"{"reason": "migration", "since": "monday", "extra": 1}"#)
+                .unwrap();
+        assert_eq!(info.reason.as_deref(), Some("migration"));
+        assert_eq!(info.describe().as_deref(), Some("migration (since monday)"));
+        assert!(MaintenanceInfo::parse("{reason").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_until() {
+        let info = MaintenanceInfo::parse("until=tomorrow").unwrap();
+        assert!(info.until_timestamp().is_err());
+    }
+
+    #[tokio::test]
+    async fn inspects_problems_of_maintenance_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.disabled");
+        std::fs::write(&file_path, "reason=deployment").unwrap();
+        let file = inspect_maintenance_file(&file_path).await.unwrap().unwrap();
+        assert_eq!(file.info.reason.as_deref(), Some("deployment"));
+        assert_eq!(file.problem, None);
+
+        std::fs::write(&file_path, "reason=deployment\nuntil=tomorrow").unwrap();
+        let file = inspect_maintenance_file(&file_path).await.unwrap().unwrap();
+        assert_eq!(file.info.reason.as_deref(), Some("deployment"));
+        assert!(file.problem.unwrap().starts_with("ignoring end"));
+
+        std::fs::write(&file_path, "{not json").unwrap();
+        let file = inspect_maintenance_file(&file_path).await.unwrap().unwrap();
+        assert_eq!(file.info, MaintenanceInfo::default());
+        assert!(file.problem.unwrap().starts_with("unable to parse details"));
+    }
+
+    #[tokio::test]
+    async fn lists_visible_drop_in_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["dba", ".dba.swp", "deploy"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        std::fs::create_dir(dir.path().join("archive")).unwrap();
+        let files = list_drop_in_files(dir.path()).await.unwrap();
+        assert_eq!(
+            files,
+            vec![dir.path().join("dba"), dir.path().join("deploy")]
+        );
+
+        let missing_dir = dir.path().join("missing");
+        assert!(list_drop_in_files(&missing_dir).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn writes_and_reads_maintenance_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("easycheck.disabled");
+        assert_eq!(read_maintenance_file(&file_path).await.unwrap(), None);
+
+        let info = MaintenanceInfo {
+            reason: Some("kernel update".to_string()),
+            until: Some("2020-01-01T00:00:00Z".to_string()),
+            ..MaintenanceInfo::default()
+        };
+        write_maintenance_file(&file_path, &info).await.unwrap();
+        let read_info = read_maintenance_file(&file_path).await.unwrap().unwrap();
+        assert_eq!(read_info, info);
+        assert!(read_info.has_ended());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
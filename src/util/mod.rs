@@ -1 +1,6 @@
+pub(crate) mod file_watcher;
+pub(crate) mod maintenance_info;
+pub(crate) mod proxy_protocol;
 pub(crate) mod tcp_connector;
+pub(crate) mod threshold;
+pub(crate) mod tls_connector;
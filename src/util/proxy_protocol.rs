@@ -0,0 +1,200 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use anyhow::bail;
+use proxy_header::{ProxiedAddress, ProxyHeader, SslInfo, Tlv};
+
+use crate::options::{Options, ProxyProtocolVersion};
+
+/// The maximum length of the unique id TLV as defined by the PROXY protocol specification.
+const MAX_UNIQUE_ID_LENGTH: usize = 128;
+
+/// The addresses and TLVs that are sent to the remote in a PROXY protocol header.
+#[derive(Debug, Clone)]
+pub(crate) struct ProxyProtocolSettings {
+    /// The source address announced in the header.
+    source_address: SocketAddr,
+    /// The destination address announced in the header.
+    destination_address: SocketAddr,
+    /// If the LOCAL command should be sent instead of the proxied addresses.
+    local_command: bool,
+    /// The additional TLVs to send, only supported by version 2.
+    tlvs: Vec<Tlv<'static>>,
+}
+
+impl Default for ProxyProtocolSettings {
+    fn default() -> Self {
+        let local_addr = SocketAddr::from_str("127.0.0.1:80").unwrap();
+        Self {
+            source_address: local_addr,
+            destination_address: local_addr,
+            local_command: false,
+            tlvs: vec![],
+        }
+    }
+}
+
+impl ProxyProtocolSettings {
+    /// Constructs the PROXY protocol settings from the given options, falling
+    /// back to the defaults for everything that wasn't configured explicitly.
+    pub fn from_options(options: &Options) -> anyhow::Result<Self> {
+        let defaults = Self::default();
+        let source_address = options
+            .proxy_protocol_source_addr
+            .unwrap_or(defaults.source_address);
+        let destination_address = options
+            .proxy_protocol_destination_addr
+            .unwrap_or(defaults.destination_address);
+        if source_address.is_ipv4() != destination_address.is_ipv4() {
+            bail!("proxy protocol source and destination address must use the same address family");
+        }
+
+        let mut tlvs = vec![];
+        if let Some(alpn) = &options.proxy_protocol_alpn {
+            tlvs.push(Tlv::Alpn(alpn.as_bytes().to_vec().into()));
+        }
+        if let Some(authority) = &options.proxy_protocol_authority {
+            tlvs.push(Tlv::Authority(authority.clone().into()));
+        }
+        if let Some(unique_id) = &options.proxy_protocol_unique_id {
+            if unique_id.len() > MAX_UNIQUE_ID_LENGTH {
+                bail!(
+                    "proxy protocol unique id must not be longer than {} bytes",
+                    MAX_UNIQUE_ID_LENGTH
+                );
+            }
+            tlvs.push(Tlv::UniqueId(unique_id.as_bytes().to_vec().into()));
+        }
+        if options.proxy_protocol_ssl_version.is_some() || options.proxy_protocol_ssl_cn.is_some() {
+            let client_cert = options.proxy_protocol_ssl_cn.is_some();
+            let mut ssl_info = SslInfo::new(true, client_cert, client_cert, 0);
+            if let Some(ssl_version) = &options.proxy_protocol_ssl_version {
+                ssl_info.append_tlv(Tlv::SslVersion(ssl_version.into()));
+            }
+            if let Some(ssl_cn) = &options.proxy_protocol_ssl_cn {
+                ssl_info.append_tlv(Tlv::SslCn(ssl_cn.into()));
+            }
+            tlvs.push(Tlv::Ssl(ssl_info.into_owned()));
+        }
+
+        Ok(Self {
+            source_address,
+            destination_address,
+            local_command: options.proxy_protocol_local.unwrap_or(false),
+            tlvs,
+        })
+    }
+}
+
+/// Encodes a PROXY protocol header in the given version that can be sent
+/// to the remote before any other data is written to the connection.
+pub(crate) fn encode_proxy_header(
+    version: &ProxyProtocolVersion,
+    settings: &ProxyProtocolSettings,
+) -> anyhow::Result<Vec<u8>> {
+    let proxied_address = if settings.local_command {
+        None
+    } else {
+        let address = ProxiedAddress::stream(settings.source_address, settings.destination_address);
+        Some(address)
+    };
+    let proxy_header = ProxyHeader::with_tlvs(proxied_address, settings.tlvs.iter().cloned());
+
+    let mut buffer = Vec::<u8>::new();
+    match version {
+        ProxyProtocolVersion::V1 => proxy_header.encode_v1(&mut buffer)?,
+        ProxyProtocolVersion::V2 => proxy_header.encode_v2(&mut buffer)?,
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use proxy_header::ParseConfig;
+
+    fn settings_from_args(args: &[&str]) -> anyhow::Result<ProxyProtocolSettings> {
+        let mut all_args = vec!["easycheck", "--bind", "127.0.0.1:0"];
+        all_args.extend_from_slice(args);
+        ProxyProtocolSettings::from_options(&Options::parse_from(all_args))
+    }
+
+    #[test]
+    fn v1_header_is_text_line() {
+        let settings = ProxyProtocolSettings::default();
+        let header = encode_proxy_header(&ProxyProtocolVersion::V1, &settings).unwrap();
+        assert_eq!(header, b"PROXY TCP4 127.0.0.1 127.0.0.1 80 80\r\n");
+    }
+
+    #[test]
+    fn v2_header_starts_with_signature() {
+        let settings = ProxyProtocolSettings::default();
+        let header = encode_proxy_header(&ProxyProtocolVersion::V2, &settings).unwrap();
+        assert!(header.starts_with(b"\r\n\r\n\0\r\nQUIT\n"));
+    }
+
+    #[test]
+    fn configured_ipv6_addresses_are_used() {
+        let settings = settings_from_args(&[
+            "--proxy-protocol-source",
+            "[2001:db8::1]:51234",
+            "--proxy-protocol-destination",
+            "[2001:db8::2]:443",
+        ])
+        .unwrap();
+        let header = encode_proxy_header(&ProxyProtocolVersion::V1, &settings).unwrap();
+        assert_eq!(header, b"PROXY TCP6 2001:db8::1 2001:db8::2 51234 443\r\n");
+    }
+
+    #[test]
+    fn mixed_address_families_are_rejected() {
+        let result = settings_from_args(&[
+            "--proxy-protocol-source",
+            "10.0.0.1:1234",
+            "--proxy-protocol-destination",
+            "[2001:db8::2]:443",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn v2_header_contains_configured_tlvs() {
+        let settings = settings_from_args(&[
+            "--proxy-protocol-alpn",
+            "h2",
+            "--proxy-protocol-authority",
+            "shop.example.com",
+            "--proxy-protocol-unique-id",
+            "easycheck",
+            "--proxy-protocol-ssl-version",
+            "TLSv1.3",
+        ])
+        .unwrap();
+        let header = encode_proxy_header(&ProxyProtocolVersion::V2, &settings).unwrap();
+
+        let (parsed, _) = ProxyHeader::parse(&header, ParseConfig::default()).unwrap();
+        assert_eq!(parsed.alpn(), Some(&b"h2"[..]));
+        assert_eq!(parsed.authority(), Some("shop.example.com"));
+        assert_eq!(parsed.unique_id(), Some(&b"easycheck"[..]));
+        let ssl_info = parsed.ssl().unwrap();
+        assert!(ssl_info.client_ssl());
+        assert_eq!(ssl_info.version(), Some("TLSv1.3"));
+    }
+
+    #[test]
+    fn v1_header_with_tlvs_is_rejected() {
+        let settings = settings_from_args(&["--proxy-protocol-authority", "example.com"]).unwrap();
+        let result = encode_proxy_header(&ProxyProtocolVersion::V1, &settings);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn local_command_has_no_address() {
+        let settings = settings_from_args(&["--proxy-protocol-local", "true"]).unwrap();
+        let header = encode_proxy_header(&ProxyProtocolVersion::V2, &settings).unwrap();
+
+        let (parsed, _) = ProxyHeader::parse(&header, ParseConfig::default()).unwrap();
+        assert!(parsed.proxied_address().is_none());
+    }
+}
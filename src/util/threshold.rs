@@ -0,0 +1,166 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A bound for a measured value, either given as an absolute amount or as
+/// a percentage of the total available amount.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Threshold {
+    /// An absolute amount, e.g. a number of bytes or inodes.
+    Absolute(u64),
+    /// A percentage of the total amount, between 0 and 100.
+    Percent(f64),
+}
+
+impl Threshold {
+    /// Checks if the given value is below this threshold. The total is used
+    /// to resolve percentage thresholds.
+    pub fn is_undercut_by(&self, value: u64, total: u64) -> bool {
+        match self {
+            Threshold::Absolute(min) => value < *min,
+            Threshold::Percent(min_percent) => percent_of(value, total) < *min_percent,
+        }
+    }
+
+    /// Checks if the given value is above this threshold. The total is used
+    /// to resolve percentage thresholds.
+    pub fn is_exceeded_by(&self, value: u64, total: u64) -> bool {
+        match self {
+            Threshold::Absolute(max) => value > *max,
+            Threshold::Percent(max_percent) => percent_of(value, total) > *max_percent,
+        }
+    }
+
+    /// Formats this threshold as a limit of bytes, so that absolute
+    /// thresholds use the same units as the measured values.
+    pub fn format_as_bytes(&self) -> String {
+        match self {
+            Threshold::Absolute(bytes) => format_bytes(*bytes),
+            Threshold::Percent(_) => self.to_string(),
+        }
+    }
+}
+
+impl FromStr for Threshold {
+    type Err = String;
+
+    /// Parses a threshold in the format `<number>%` for percentages or
+    /// `<number>[K|M|G|T]` for absolute values with optional binary unit.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(percent) = value.strip_suffix('%') {
+            let percent = percent
+                .trim()
+                .parse::<f64>()
+                .map_err(|err| format!("invalid percentage {}: {}", value, err))?;
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(format!("percentage {} must be between 0 and 100", value));
+            }
+            return Ok(Threshold::Percent(percent));
+        }
+
+        let (number, multiplier) = match value.char_indices().last() {
+            Some((index, unit)) if unit.is_ascii_alphabetic() => {
+                let multiplier = match unit.to_ascii_uppercase() {
+                    'K' => 1u64 << 10,
+                    'M' => 1u64 << 20,
+                    'G' => 1u64 << 30,
+                    'T' => 1u64 << 40,
+                    _ => return Err(format!("unknown unit {} in {}", unit, value)),
+                };
+                (&value[..index], multiplier)
+            }
+            _ => (value, 1),
+        };
+        let number = number
+            .trim()
+            .parse::<u64>()
+            .map_err(|err| format!("invalid threshold {}: {}", value, err))?;
+        number
+            .checked_mul(multiplier)
+            .map(Threshold::Absolute)
+            .ok_or_else(|| format!("threshold {} is too large", value))
+    }
+}
+
+impl Display for Threshold {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Threshold::Absolute(value) => write!(f, "{}", value),
+            Threshold::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+/// Calculates the percentage that the given value takes of the total.
+/// Returns 100 if the total is zero, as nothing can be used up in that case.
+pub(crate) fn percent_of(value: u64, total: u64) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        value as f64 * 100.0 / total as f64
+    }
+}
+
+/// Formats the given amount of bytes in a human-readable way using binary units.
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit_index = 0;
+    while value >= 1024.0 && unit_index < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit_index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_percentages() {
+        assert_eq!("5%".parse::<Threshold>(), Ok(Threshold::Percent(5.0)));
+        assert_eq!("12.5%".parse::<Threshold>(), Ok(Threshold::Percent(12.5)));
+        assert!("120%".parse::<Threshold>().is_err());
+    }
+
+    #[test]
+    fn parses_absolute_values_with_units() {
+        assert_eq!("1000".parse::<Threshold>(), Ok(Threshold::Absolute(1000)));
+        assert_eq!("2K".parse::<Threshold>(), Ok(Threshold::Absolute(2048)));
+        assert_eq!("1g".parse::<Threshold>(), Ok(Threshold::Absolute(1 << 30)));
+        assert!("5X".parse::<Threshold>().is_err());
+        assert!("abc".parse::<Threshold>().is_err());
+    }
+
+    #[test]
+    fn formats_byte_thresholds() {
+        assert_eq!(Threshold::Absolute(10 << 30).format_as_bytes(), "10.0 GiB");
+        assert_eq!(Threshold::Absolute(10 << 30).to_string(), "10737418240");
+        assert_eq!(Threshold::Percent(5.0).format_as_bytes(), "5%");
+    }
+
+    #[test]
+    fn threshold_evaluation() {
+        assert!(Threshold::Absolute(100).is_undercut_by(99, 1000));
+        assert!(!Threshold::Absolute(100).is_undercut_by(100, 1000));
+        assert!(Threshold::Percent(10.0).is_undercut_by(99, 1000));
+        assert!(!Threshold::Percent(10.0).is_undercut_by(100, 1000));
+        assert!(Threshold::Absolute(100).is_exceeded_by(101, 1000));
+        assert!(!Threshold::Absolute(100).is_exceeded_by(100, 1000));
+        assert!(Threshold::Percent(10.0).is_exceeded_by(101, 1000));
+        assert!(!Threshold::Percent(10.0).is_exceeded_by(100, 1000));
+    }
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 << 30), "3.0 GiB");
+    }
+}
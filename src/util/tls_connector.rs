@@ -0,0 +1,143 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use tokio_rustls::rustls::crypto::ring::default_provider;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector as RustlsConnector;
+
+use crate::util::tcp_connector::AsyncStream;
+
+/// A CA file containing a single self-signed certificate, used by tests.
+#[cfg(test)]
+pub(crate) const TEST_CA_FILE_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/test-ca.pem");
+
+/// Wraps already established connections into a TLS session. The server
+/// certificate is always verified, either against the system trust store
+/// or against the certificates from a provided CA file.
+pub(crate) struct TlsConnector {
+    connector: RustlsConnector,
+    server_name: ServerName<'static>,
+}
+
+impl TlsConnector {
+    /// Constructs a new TLS connector. If no server name is given, the ip
+    /// address of the target is used for SNI and certificate verification.
+    pub fn new(
+        target_address: &SocketAddr,
+        server_name: Option<&str>,
+        ca_file_path: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        let root_store = match ca_file_path {
+            Some(ca_file_path) => Self::load_ca_file(ca_file_path)?,
+            None => Self::load_native_roots()?,
+        };
+        let client_config = ClientConfig::builder_with_provider(Arc::new(default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+
+        let server_name = match server_name {
+            Some(server_name) => ServerName::try_from(server_name.to_string())
+                .with_context(|| format!("invalid tls server name {}", server_name))?,
+            None => ServerName::IpAddress(target_address.ip().into()),
+        };
+
+        Ok(Self {
+            connector: RustlsConnector::from(Arc::new(client_config)),
+            server_name,
+        })
+    }
+
+    /// Performs the TLS handshake on the given stream and returns the
+    /// encrypted stream on success.
+    pub async fn wrap(
+        &self,
+        stream: Pin<Box<dyn AsyncStream>>,
+    ) -> std::io::Result<Pin<Box<dyn AsyncStream>>> {
+        let tls_stream = self
+            .connector
+            .connect(self.server_name.clone(), stream)
+            .await?;
+        Ok(Box::pin(tls_stream))
+    }
+
+    fn load_ca_file(ca_file_path: &Path) -> anyhow::Result<RootCertStore> {
+        let mut root_store = RootCertStore::empty();
+        let certificates = CertificateDer::pem_file_iter(ca_file_path)
+            .with_context(|| format!("unable to read tls ca file {:?}", ca_file_path))?;
+        for certificate in certificates {
+            let certificate = certificate.with_context(|| {
+                format!("invalid certificate in tls ca file {:?}", ca_file_path)
+            })?;
+            root_store.add(certificate)?;
+        }
+        if root_store.is_empty() {
+            bail!("tls ca file {:?} contains no certificates", ca_file_path);
+        }
+        Ok(root_store)
+    }
+
+    fn load_native_roots() -> anyhow::Result<RootCertStore> {
+        let native_certs = rustls_native_certs::load_native_certs();
+        for error in &native_certs.errors {
+            log::warn!("unable to load native root certificate: {}", error);
+        }
+
+        let mut root_store = RootCertStore::empty();
+        let (_, ignored) = root_store.add_parsable_certificates(native_certs.certs);
+        if ignored > 0 {
+            log::debug!("ignored {} unparsable native root certificates", ignored);
+        }
+        Ok(root_store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn dummy_addr() -> SocketAddr {
+        "127.0.0.1:9999".parse().unwrap()
+    }
+
+    #[test]
+    fn missing_ca_file_returns_error() {
+        let result = TlsConnector::new(
+            &dummy_addr(),
+            None,
+            Some(Path::new("/tmp/easycheck_nonexistent_ca_file")),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn ca_file_without_certificates_returns_error() {
+        let mut ca_file = NamedTempFile::new().unwrap();
+        ca_file.write_all(b"no certificates here\n").unwrap();
+        let result = TlsConnector::new(&dummy_addr(), None, Some(ca_file.path()));
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains("contains no certificates"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_server_name_returns_error() {
+        let ca_file_path = Path::new(TEST_CA_FILE_PATH);
+        let result = TlsConnector::new(&dummy_addr(), Some("not a hostname!"), Some(ca_file_path));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn server_name_defaults_to_ip_address() {
+        let ca_file_path = Path::new(TEST_CA_FILE_PATH);
+        let connector = TlsConnector::new(&dummy_addr(), None, Some(ca_file_path)).unwrap();
+        assert_eq!(connector.server_name.to_str(), "127.0.0.1");
+    }
+}
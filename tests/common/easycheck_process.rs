@@ -24,6 +24,14 @@ impl EasycheckProcess {
     /// Starts the easycheck binary with `--bind 127.0.0.1:<port> --revalidation-interval 1`
     /// plus any extra arguments provided by the test.
     pub fn start(extra_args: &[&str]) -> Self {
+        Self::start_with_revalidation_interval(REVALIDATION_INTERVAL_SECS, extra_args)
+    }
+
+    /// Starts easycheck with the given interval between the check cycles.
+    pub fn start_with_revalidation_interval(
+        revalidation_interval: u64,
+        extra_args: &[&str],
+    ) -> Self {
         let port = allocate_port();
         let bind = format!("127.0.0.1:{}", port);
 
@@ -31,7 +39,7 @@ impl EasycheckProcess {
             .arg("--bind")
             .arg(&bind)
             .arg("--revalidation-interval")
-            .arg(REVALIDATION_INTERVAL_SECS.to_string())
+            .arg(revalidation_interval.to_string())
             .args(extra_args)
             .stdout(Stdio::null())
             .stderr(Stdio::null())
@@ -63,7 +71,7 @@ impl EasycheckProcess {
         }
     }
 
-    /// Polls GET / until the response body no longer contains "Initial Check",
+    /// Polls GET / until the response body no longer contains pending checks,
     /// indicating at least one real check cycle has completed.
     pub async fn wait_for_check_cycle(&self) {
         let client = reqwest::Client::new();
@@ -77,7 +85,7 @@ impl EasycheckProcess {
             }
             if let Ok(resp) = client.get(self.base_url()).send().await {
                 if let Ok(body) = resp.text().await {
-                    if !body.contains("Initial Check") {
+                    if !body.contains("waiting for the first result") {
                         return;
                     }
                 }
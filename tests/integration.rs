@@ -5,7 +5,7 @@ use common::mock_http_server::MockHttpServer;
 use common::mock_proxy_http_server::MockProxyProtocolHttpServer;
 use common::mock_tcp_server::MockTcpServer;
 
-/// Before the first check cycle completes, easycheck returns 503 with "Initial Check".
+/// Before the first check cycle completes, easycheck returns 503 listing the pending checks.
 #[tokio::test]
 async fn initial_state_returns_503() {
     // Start a TCP listener that accepts connections but never responds,
@@ -31,7 +31,37 @@ async fn initial_state_returns_503() {
     let resp = reqwest::get(&proc.base_url()).await.unwrap();
     assert_eq!(resp.status().as_u16(), 503);
     let body = resp.text().await.unwrap();
-    assert!(body.contains("Initial Check"));
+    assert!(body.contains("http endpoint check"), "{}", body);
+    assert!(body.contains("waiting for the first result"), "{}", body);
+}
+
+/// A check with the initial state up is assumed passing until its first result.
+#[tokio::test]
+async fn initial_state_up_returns_200_before_first_result() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hanging_port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            if let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let _keep_alive = stream;
+                    tokio::time::sleep(std::time::Duration::from_secs(300)).await;
+                });
+            }
+        }
+    });
+
+    let url = format!("http://127.0.0.1:{}/", hanging_port);
+    let proc = EasycheckProcess::start(&[
+        "--http-url",
+        &url,
+        "--check-initial-state",
+        "http=up,mtc=up,force-success=up",
+    ]);
+    proc.wait_for_ready().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
 }
 
 /// With no failing checks configured, easycheck becomes healthy after the first cycle.
@@ -61,6 +91,422 @@ async fn mtc_file_present_returns_503() {
     assert!(body.contains("mtc file"));
 }
 
+/// Maintenance is answered with the configured status code, body format and headers.
+#[tokio::test]
+async fn mtc_file_present_uses_configured_response() {
+    let mtc_file = tempfile::NamedTempFile::new().unwrap();
+    let mtc_path = mtc_file.path().to_str().unwrap().to_string();
+
+    let proc = EasycheckProcess::start(&[
+        "--mtc-file-path",
+        &mtc_path,
+        "--maintenance-status-code",
+        "418",
+        "--body-format",
+        "text",
+        "--response-header",
+        "Cache-Control: no-store",
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 418);
+    assert_eq!(resp.headers()["cache-control"], "no-store");
+    let body = resp.text().await.unwrap();
+    assert_eq!(body, "MAINTENANCE\nmtc file: mtc file exists\n");
+}
+
+/// The details of the maintenance file are listed, an expired maintenance is ignored.
+#[tokio::test]
+async fn mtc_file_details_are_listed_until_expired() {
+    let mtc_file = tempfile::NamedTempFile::new().unwrap();
+    let mtc_path = mtc_file.path().to_str().unwrap().to_string();
+    std::fs::write(&mtc_path, "reason=kernel update\nby=jane\n").unwrap();
+
+    let proc = EasycheckProcess::start(&["--mtc-file-path", &mtc_path, "--body-format", "text"]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body = resp.text().await.unwrap();
+    assert_eq!(
+        body,
+        "MAINTENANCE\nmtc file: mtc file exists: kernel update (by jane)\n"
+    );
+
+    std::fs::write(
+        &mtc_path,
+        r#"{"reason": "kernel update", "until": "2020-01-01T00:00:00Z"}"#,
+    )
+    .unwrap();
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+/// Each file in the maintenance directory is a separate hold, the service is
+/// available again once all holds are removed.
+#[tokio::test]
+async fn mtc_dir_files_are_separate_holds() {
+    let mtc_dir = tempfile::tempdir().unwrap();
+    let mtc_dir_path = mtc_dir.path().to_str().unwrap().to_string();
+    std::fs::write(mtc_dir.path().join("dba"), "reason=index rebuild").unwrap();
+    std::fs::write(mtc_dir.path().join("deploy"), "").unwrap();
+
+    let proc = EasycheckProcess::start(&[
+        "--mtc-file-path",
+        "/tmp/easycheck_test_nonexistent_mtc_file",
+        "--mtc-dir-path",
+        &mtc_dir_path,
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let check_names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|failing_check| failing_check["check_name"].as_str().unwrap())
+        .collect();
+    assert_eq!(check_names, vec!["mtc file dba", "mtc file deploy"]);
+
+    std::fs::remove_file(mtc_dir.path().join("dba")).unwrap();
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+
+    std::fs::remove_file(mtc_dir.path().join("deploy")).unwrap();
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+/// Creating and removing the maintenance file is detected without waiting for
+/// the next check cycle.
+#[tokio::test]
+async fn mtc_file_changes_are_detected_immediately() {
+    let mtc_dir = tempfile::tempdir().unwrap();
+    let mtc_path = mtc_dir.path().join("easycheck.disabled");
+
+    let proc = EasycheckProcess::start_with_revalidation_interval(
+        60,
+        &["--mtc-file-path", mtc_path.to_str().unwrap()],
+    );
+    proc.wait_for_check_cycle().await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    std::fs::write(&mtc_path, "reason=deployment").unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+
+    std::fs::remove_file(&mtc_path).unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+/// The maintenance is toggled through the authenticated admin api.
+#[tokio::test]
+async fn admin_api_toggles_maintenance() {
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join("admin-tokens");
+    std::fs::write(&token_path, "# deployment orchestrator\nsecret-token\n").unwrap();
+    let mtc_path = dir.path().join("easycheck.disabled");
+
+    let proc = EasycheckProcess::start(&[
+        "--admin-token-file",
+        token_path.to_str().unwrap(),
+        "--mtc-file-path",
+        mtc_path.to_str().unwrap(),
+    ]);
+    proc.wait_for_check_cycle().await;
+    let admin_url = format!("{}/admin/maintenance", proc.base_url());
+    let client = reqwest::Client::new();
+
+    let resp = client.post(&admin_url).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let resp = client
+        .post(&admin_url)
+        .bearer_auth("wrong-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let resp = client
+        .post(&admin_url)
+        .bearer_auth("secret-token")
+        .json(&serde_json::json!({"ttl": 0}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+    assert!(!mtc_path.exists());
+
+    let resp = client
+        .post(&admin_url)
+        .bearer_auth("secret-token")
+        .json(&serde_json::json!({"reason": "kernel update", "by": "jane", "ttl": 600}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["enabled"], true);
+    assert_eq!(body["reason"], "kernel update");
+    assert!(body["until"].is_string());
+
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body = resp.text().await.unwrap();
+    assert!(body.contains("kernel update (by jane"), "{}", body);
+
+    let resp = client
+        .delete(&admin_url)
+        .bearer_auth("secret-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(!mtc_path.exists());
+
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+/// A drain file reports the drain state with its own status code and headers,
+/// and can be toggled through the admin api.
+#[tokio::test]
+async fn drain_file_reports_drain_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join("admin-tokens");
+    std::fs::write(&token_path, "secret-token\n").unwrap();
+    let drain_path = dir.path().join("easycheck.drain");
+
+    let proc = EasycheckProcess::start(&[
+        "--admin-token-file",
+        token_path.to_str().unwrap(),
+        "--mtc-file-path",
+        dir.path().join("easycheck.disabled").to_str().unwrap(),
+        "--drain-file-path",
+        drain_path.to_str().unwrap(),
+        "--drain-response-header",
+        "Connection: close",
+    ]);
+    proc.wait_for_check_cycle().await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(resp.headers().get("connection").is_none());
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/admin/drain", proc.base_url()))
+        .bearer_auth("secret-token")
+        .json(&serde_json::json!({"reason": "deployment"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(drain_path.exists());
+
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+    assert_eq!(resp.headers()["x-easycheck-state"], "drain");
+    assert_eq!(resp.headers()["connection"], "close");
+    let body = resp.text().await.unwrap();
+    assert!(body.contains("drain file exists: deployment"), "{}", body);
+
+    std::fs::remove_file(&drain_path).unwrap();
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+/// The admin api is only served on its own bind address if configured.
+#[tokio::test]
+async fn admin_api_uses_separate_bind() {
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join("admin-tokens");
+    std::fs::write(&token_path, "secret-token").unwrap();
+    let force_success_path = dir.path().join("easycheck.success");
+    let admin_bind = format!("127.0.0.1:{}", allocate_port());
+
+    let proc = EasycheckProcess::start(&[
+        "--admin-token-file",
+        token_path.to_str().unwrap(),
+        "--admin-bind",
+        &admin_bind,
+        "--force-success-file-path",
+        force_success_path.to_str().unwrap(),
+    ]);
+    proc.wait_for_check_cycle().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{}/admin/force-success", proc.base_url()))
+        .bearer_auth("secret-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let resp = client
+        .post(format!("http://{}/admin/force-success", admin_bind))
+        .bearer_auth("secret-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(force_success_path.exists());
+
+    let resp = client
+        .get(format!("http://{}/admin/force-success", admin_bind))
+        .bearer_auth("secret-token")
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["enabled"], true);
+    assert_eq!(body["by"], "admin api");
+}
+
+/// Runs the maintenance subcommand of easycheck, returns its standard output.
+fn run_maintenance_command(global_args: &[&str], args: &[&str]) -> String {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_easycheck"))
+        .args(global_args)
+        .arg("maintenance")
+        .args(args)
+        .output()
+        .expect("failed to run easycheck maintenance");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The maintenance subcommand changes the maintenance file and waits for the new state.
+#[tokio::test]
+async fn maintenance_command_toggles_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let mtc_path = dir.path().join("easycheck.disabled");
+    let mtc_path = mtc_path.to_str().unwrap();
+
+    let proc = EasycheckProcess::start(&["--mtc-file-path", mtc_path]);
+    proc.wait_for_check_cycle().await;
+    let status_url = proc.base_url();
+    let global_args = ["--mtc-file-path", mtc_path];
+
+    // the command runs in its own process, blocking the test is fine
+    let output = run_maintenance_command(
+        &global_args,
+        &[
+            "on",
+            "--reason",
+            "kernel update",
+            "--by",
+            "jane",
+            "--for",
+            "30m",
+            "--wait",
+            "--status-url",
+            &status_url,
+        ],
+    );
+    assert!(
+        output.starts_with("maintenance on: kernel update (by jane, since "),
+        "{}",
+        output
+    );
+    let resp = reqwest::get(&status_url).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    assert_eq!(resp.headers()["x-easycheck-state"], "maintenance");
+
+    let output = run_maintenance_command(&global_args, &["status"]);
+    assert!(
+        output.starts_with("maintenance on: kernel update"),
+        "{}",
+        output
+    );
+
+    let output = run_maintenance_command(
+        &global_args,
+        &["off", "--wait", "--status-url", &status_url],
+    );
+    assert_eq!(output, "maintenance off\n");
+    let resp = reqwest::get(&status_url).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["x-easycheck-state"], "up");
+}
+
+/// The maintenance subcommand toggles the maintenance through the admin api.
+#[tokio::test]
+async fn maintenance_command_uses_admin_api() {
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join("admin-tokens");
+    std::fs::write(&token_path, "secret-token").unwrap();
+    let token_path = token_path.to_str().unwrap();
+    let mtc_path = dir.path().join("easycheck.disabled");
+
+    let proc = EasycheckProcess::start(&[
+        "--admin-token-file",
+        token_path,
+        "--mtc-file-path",
+        mtc_path.to_str().unwrap(),
+    ]);
+    proc.wait_for_check_cycle().await;
+    let admin_url = proc.base_url();
+    let global_args = ["--admin-token-file", token_path];
+
+    let output = run_maintenance_command(
+        &global_args,
+        &["on", "--reason", "deployment", "--admin-url", &admin_url],
+    );
+    assert!(
+        output.starts_with("maintenance on: deployment"),
+        "{}",
+        output
+    );
+    assert!(mtc_path.exists());
+
+    let output = run_maintenance_command(&global_args, &["status", "--admin-url", &admin_url]);
+    assert!(
+        output.starts_with("maintenance on: deployment"),
+        "{}",
+        output
+    );
+
+    let output = run_maintenance_command(&global_args, &["off", "--admin-url", &admin_url]);
+    assert_eq!(output, "maintenance off\n");
+    assert!(!mtc_path.exists());
+}
+
+/// The body template is rendered with the state of the service.
+#[tokio::test]
+async fn body_template_is_rendered() {
+    let proc = EasycheckProcess::start(&[
+        "--mtc-file-path",
+        "/tmp/easycheck_test_nonexistent_mtc_file",
+        "--body-format",
+        "template",
+        "--body-template",
+        "{state} ({failing_check_count} failing)",
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.text().await.unwrap(), "up (0 failing)");
+}
+
 /// When the maintenance file does not exist, easycheck returns 200.
 #[tokio::test]
 async fn mtc_file_absent_returns_200() {
@@ -138,6 +584,142 @@ async fn http_check_unhealthy_backend() {
     assert!(body.contains("http endpoint check"));
 }
 
+/// A group of http checks keeps the server available while its policy is met.
+#[tokio::test]
+async fn check_group_quorum_keeps_200() {
+    let healthy = MockHttpServer::start(200).await;
+    let unhealthy = MockHttpServer::start(500).await;
+    let (healthy_url, unhealthy_url) = (healthy.url(), unhealthy.url());
+
+    let proc = EasycheckProcess::start(&[
+        "--http-url",
+        &healthy_url,
+        "--http-url",
+        &healthy_url,
+        "--http-url",
+        &unhealthy_url,
+        "--check-group",
+        "api=http.1+http.2+http.3",
+        "--check-group-policy",
+        "api=2",
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body = resp.text().await.unwrap();
+    assert!(
+        body.contains("group api has 2 of 3 checks passing"),
+        "{}",
+        body
+    );
+
+    healthy.set_status(500);
+    tokio::time::sleep(NEXT_CYCLE_WAIT).await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+}
+
+/// Endpoints only derive their status from their own checks.
+#[tokio::test]
+async fn endpoints_use_own_checks_and_status_codes() {
+    let mock = MockHttpServer::start(500).await;
+    let url = mock.url();
+
+    let proc = EasycheckProcess::start(&[
+        "--http-url",
+        &url,
+        "--endpoint",
+        "/live=mtc,/ready=mtc+http",
+        "--endpoint-status-code",
+        "/ready:down=500",
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(format!("{}/live", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = reqwest::get(format!("{}/ready", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 500);
+    let body = resp.text().await.unwrap();
+    assert!(body.contains("http endpoint check"), "{}", body);
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+}
+
+/// Each endpoint answers maintenance with its own status code.
+#[tokio::test]
+async fn endpoints_use_own_maintenance_status_codes() {
+    let mtc_file = tempfile::NamedTempFile::new().unwrap();
+    let mtc_path = mtc_file.path().to_str().unwrap().to_string();
+
+    let proc = EasycheckProcess::start(&[
+        "--mtc-file-path",
+        &mtc_path,
+        "--endpoint",
+        "/live=mtc,/ready=mtc",
+        "--endpoint-status-code",
+        "/live:maintenance=200,/ready:maintenance=418",
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(format!("{}/live", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["x-easycheck-state"], "maintenance");
+
+    let resp = reqwest::get(format!("{}/ready", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 418);
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+}
+
+/// Query parameters select the checks the status is derived from.
+#[tokio::test]
+async fn query_parameters_filter_checks() {
+    let mock = MockHttpServer::start(500).await;
+    let url = mock.url();
+
+    let proc = EasycheckProcess::start(&["--http-url", &url]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(format!("{}/?exclude=http", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = reqwest::get(format!("{}/?include=http,mtc", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+
+    let resp = reqwest::get(format!("{}/?include=disk", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let resp = reqwest::get(format!("{}/?verbose", proc.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["failing_checks"].as_array().unwrap().len(), 1);
+    let checks = body["checks"].as_array().unwrap();
+    assert!(checks
+        .iter()
+        .any(|check| check["check_id"] == "mtc" && check["severity"] == "ok"));
+}
+
 /// HTTP check accepts custom status codes (e.g. 204).
 #[tokio::test]
 async fn http_check_custom_status_codes() {
@@ -255,6 +837,34 @@ async fn socket_check_no_banner_with_read_initial_response() {
     assert!(body.contains("network connection check"));
 }
 
+/// Checks depending on a failed check are skipped and reported as such.
+#[tokio::test]
+async fn check_with_failed_dependency_is_skipped() {
+    let mock = MockHttpServer::start(200).await;
+    let url = mock.url();
+    let addr = format!("127.0.0.1:{}", allocate_port());
+
+    let proc = EasycheckProcess::start(&[
+        "--http-url",
+        &url,
+        "--socket-addr",
+        &addr,
+        "--check-depends-on",
+        "http=socket",
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body = resp.text().await.unwrap();
+    assert!(body.contains("network connection check"), "{}", body);
+    assert!(
+        body.contains("skipped: dependency socket failed"),
+        "{}",
+        body
+    );
+}
+
 /// Socket check fails when no TCP server is running (connection refused).
 #[tokio::test]
 async fn socket_check_connection_refused() {
@@ -310,3 +920,53 @@ async fn multiple_checks_one_fails() {
         body
     );
 }
+
+/// A check failing with a warning severity is reported in the body and header, but keeps 200.
+#[tokio::test]
+async fn warning_severity_keeps_200() {
+    let dead_port = allocate_port();
+    let tcp_addr = format!("127.0.0.1:{}", dead_port);
+
+    let proc = EasycheckProcess::start(&[
+        "--socket-addr",
+        &tcp_addr,
+        "--check-severity",
+        "socket=warning",
+    ]);
+    proc.wait_for_check_cycle().await;
+
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(
+        resp.headers()
+            .get("x-easycheck-severity")
+            .and_then(|value| value.to_str().ok()),
+        Some("warning")
+    );
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body[0]["severity"], "warning");
+}
+
+/// When the check cycle stalls longer than the maximum result age, easycheck returns 503.
+#[tokio::test]
+async fn stalled_check_cycle_returns_stale_status() {
+    let proc = EasycheckProcess::start(&[
+        "--exec-command",
+        "sleep",
+        "--exec-arg",
+        "30",
+        "--exec-timeout",
+        "60",
+        "--check-initial-state",
+        "exec=up,mtc=up,force-success=up",
+        "--max-result-age",
+        "1",
+    ]);
+    proc.wait_for_ready().await;
+
+    tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+    let resp = reqwest::get(&proc.base_url()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body = resp.text().await.unwrap();
+    assert!(body.contains("status stale"), "{}", body);
+}